@@ -1,4 +1,4 @@
-use exact_cover::{Problem, Solver, SolverEvent};
+use exact_cover::{Problem, Solver};
 
 fn main() {
     let mut prob = Problem::default();
@@ -10,15 +10,7 @@ fn main() {
     prob.add_subset("C", vec![1, 2]);
     prob.add_subset("D", vec![2, 3]);
 
-    let mut solver = Solver::new(prob);
-    let mut solutions = vec![];
-    solver.run();
-
-    for event in solver {
-        if let SolverEvent::SolutionFound(sol) = event {
-            solutions.push(sol);
-        }
-    }
+    let solutions = Solver::new(prob).solve_all().unwrap();
 
     println!("{:?}", solutions);
 }
@@ -1,39 +1,7 @@
 use std::error::Error;
 use std::time::Instant;
-use exact_cover::vector::Vector2D;
-use exact_cover::problems::polyomino::{Polyomino, PolyominoPacking, Cell, Board, CompoundName};
-use exact_cover::{Solver, SolverEvent};
-
-fn print_sol(prob: &PolyominoPacking<&str>, sol: &Vec<CompoundName<&str>>) {
-    let mut buff = Vec::new();
-    
-    for y in 0..prob.board().size().y {
-        let row: Vec<char> = prob.board().cells()[y as usize].iter()
-            .map(|c| {
-                match *c {
-                    Cell::Filled => '.',
-                    Cell::Wildcard => '.',
-                    Cell::Empty => ' ',
-                }
-            })
-            .collect();
-        buff.push(row);
-    }
-
-    for &(name, o, t) in sol {
-        let cells = prob.pieces()[name].orient(o).translated_cells(t);
-        for Vector2D { x, y } in cells {
-            buff[y as usize][x as usize] = name.chars().nth(0).unwrap();
-        }
-    }
-
-    for y in 0..buff.len() {
-        for x in 0..buff[y].len() {
-            print!("{} ", buff[y][x]);
-        }
-        println!();
-    }
-}
+use exact_cover::problems::polyomino::{Polyomino, PolyominoPacking, Board};
+use exact_cover::Solver;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let board = Board::from_bytes_array(&[
@@ -51,8 +19,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         b".#.",
     ])?;
     
-    let mut prob = PolyominoPacking::default();
-    *prob.board_mut() = board;
+    let mut prob = PolyominoPacking::new();
+    prob.set_board(board);
     prob.add_piece("O", tet_o);
     prob.set_piece_range("O", 0, 2);
     prob.add_piece("T", tet_t);
@@ -60,19 +28,16 @@ fn main() -> Result<(), Box<dyn Error>> {
     
     println!("Generating the problem...");
     let gen_prob = prob.generate_problem();
-    let mut solver = Solver::new(gen_prob);
+    let solver = Solver::new(gen_prob);
     
     println!("Solving the problem...");
     let start_time = Instant::now();
     let mut solutions = vec![];
-    solver.run();
     
-    for event in solver {
-        if let SolverEvent::SolutionFound(sol) = event {
-            print_sol(&prob, &sol);
-            println!();
-            solutions.push(sol);
-        }
+    for sol in solver.solutions()? {
+        print!("{}", prob.render_solution(&sol));
+        println!();
+        solutions.push(sol);
     }
 
     // This does not measure the exact time because printing the solutions takes up a nonnegligible fraction.
@@ -5,20 +5,27 @@ pub trait Callback<M> {
     fn on_solution(&mut self, _sol: Vec<usize>, _mat: &mut M) {}
     fn on_iteration(&mut self, _mat: &mut M) {}
     fn on_abort(&mut self, _mat: &mut M) {}
-    fn on_finish(&mut self) {}
+    /// Called when the search has explored `branch` out of `total` top-level branches.
+    fn on_branch_advanced(&mut self, _branch: usize, _total: usize, _mat: &mut M) {}
+    /// Called when row `row` is selected to cover column `col` (both 1-based).
+    fn on_branch(&mut self, _col: usize, _row: usize, _mat: &mut M) {}
+    /// Called when the last selected row is unselected.
+    fn on_backtrack(&mut self, _mat: &mut M) {}
+    /// Called when column `col` (1-based) is chosen, but it cannot be covered enough times
+    /// by the remaining rows, so the search backtracks.
+    fn on_dead_end(&mut self, _col: usize, _mat: &mut M) {}
+    fn on_finish(&mut self, _mat: &mut M) {}
 }
 
+/// A callback that does nothing.
+impl<M> Callback<M> for () {}
+
 /// A simple callback that just collects solutions into a vector.
+#[derive(Default)]
 pub struct SolutionCallback {
     pub solutions: Vec<Vec<usize>>,
 }
 
-impl Default for SolutionCallback {
-    fn default() -> SolutionCallback {
-        SolutionCallback { solutions: vec![] }
-    }
-}
-
 impl<M> Callback<M> for SolutionCallback {
     fn on_solution(&mut self, sol: Vec<usize>, _mat: &mut M) {
         self.solutions.push(sol);
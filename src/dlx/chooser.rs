@@ -0,0 +1,133 @@
+//! Column selection heuristics to pass to the DLX algorithm.
+//!
+//! The choice of column does not affect the set of solutions,
+//! but it may affect the order of the solutions and the size of the search tree.
+
+use crate::dlx::dlx_m::Matrix;
+
+/// An interface of column selection heuristics.
+pub trait ColumnChooser<M> {
+    /// Chooses a column to branch on among the remaining columns of `mat`.
+    /// It returns 0 if there are no columns left.
+    fn choose(&self, mat: &M) -> usize;
+}
+
+/// MRV (minimum remaining values) heuristic, the default one.
+///
+/// It chooses a column with the fewest rows, i.e. the minimal branching factor.
+/// Among such columns, it chooses the one with the smallest index,
+/// so the order of the solutions only depends on the matrix itself.
+/// The solution order of [`Problem`](crate::Problem) relies on this tie-breaking.
+#[derive(Default, Clone, Copy)]
+pub struct Mrv;
+
+impl ColumnChooser<Matrix> for Mrv {
+    fn choose(&self, mat: &Matrix) -> usize {
+        mat.columns()
+            .min_by_key(|&c| (mat.column_size(c), c))
+            .unwrap_or(0)
+    }
+}
+
+/// Chooses the first column in the current column list.
+#[derive(Default, Clone, Copy)]
+pub struct FirstColumn;
+
+impl ColumnChooser<Matrix> for FirstColumn {
+    fn choose(&self, mat: &Matrix) -> usize {
+        mat.columns().next().unwrap_or(0)
+    }
+}
+
+/// Chooses the column containing the smallest remaining row.
+///
+/// Unless the rows are shuffled, the rows of each column are in increasing order,
+/// so the smallest remaining row is always tried first.
+/// It helps to find a lexicographically small solution early
+/// (see [`Solver::min_solution`](crate::Solver::min_solution)).
+/// The columns without rows are chosen first, since they need no branching.
+#[derive(Default, Clone, Copy)]
+pub struct SmallestRow;
+
+impl ColumnChooser<Matrix> for SmallestRow {
+    fn choose(&self, mat: &Matrix) -> usize {
+        mat.columns()
+            .min_by_key(|&c| (mat.column_first_row(c).unwrap_or(0), c))
+            .unwrap_or(0)
+    }
+}
+
+/// MRV heuristic with deterministic tie-breaking.
+///
+/// Among the columns with the fewest rows, it prefers the one
+/// that still needs the most rows to reach its minimum multiplicity,
+/// and then the one with the smallest index.
+#[derive(Default, Clone, Copy)]
+pub struct MostConstrained;
+
+impl ColumnChooser<Matrix> for MostConstrained {
+    fn choose(&self, mat: &Matrix) -> usize {
+        mat.columns()
+            .min_by_key(|&c| {
+                let (min, _) = mat.column_multiplicity(c);
+                let demand = min.saturating_sub(mat.column_weight(c));
+                (mat.column_size(c), std::cmp::Reverse(demand), c)
+            })
+            .unwrap_or(0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dlx::callback::SolutionCallback;
+
+    fn solutions(chooser: impl ColumnChooser<Matrix>) -> Vec<Vec<usize>> {
+        // The README example with the first two columns swapped,
+        // so that the first column is not the smallest one.
+        let mut mat = Matrix::with_rows(3, &[
+            &[2, 1, 3], // A
+            &[2],       // B
+            &[1],       // C
+            &[3],       // D
+            &[2, 1],    // E
+            &[1, 3],    // F
+        ]).unwrap();
+        let mut callback = SolutionCallback::default();
+        mat.solve_with(&mut callback, &chooser);
+        callback.solutions
+    }
+
+    fn sorted(mut solutions: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        for sol in &mut solutions { sol.sort_unstable(); }
+        solutions.sort();
+        solutions
+    }
+
+    #[test]
+    fn mrv_breaks_ties_by_smallest_index() {
+        let mut mat = Matrix::with_rows(4, &[&[4, 3], &[2], &[4, 3, 1], &[1, 2]]).unwrap();
+        assert_eq!(Mrv.choose(&mat), 1);
+
+        mat.disable_row(1);
+        assert_eq!(Mrv.choose(&mat), 3);
+    }
+
+    #[test]
+    fn choosers_find_all_solutions() {
+        let mrv = solutions(Mrv);
+        assert_eq!(mrv, vec![vec![1], vec![2, 3, 4], vec![2, 6], vec![5, 4]]);
+
+        let first = solutions(FirstColumn);
+        assert_ne!(first, mrv);
+        assert_eq!(sorted(first), sorted(mrv.clone()));
+
+        let most_constrained = solutions(MostConstrained);
+        assert_eq!(sorted(most_constrained), sorted(mrv.clone()));
+
+        let smallest_row = solutions(SmallestRow);
+        assert_eq!(smallest_row[0], vec![1]);
+        assert_eq!(sorted(smallest_row), sorted(mrv));
+    }
+}
@@ -1,9 +1,10 @@
 //! A low-level API for original dancing links (DLX) algorithm.
 
+use crate::dlx::{MatrixError, ParseMatrixError, SparseMatrix};
 use crate::dlx::callback::{Callback};
 
 /// A single node of [`Matrix`].
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Eq)]
 #[cfg_attr(test, derive(Debug))]
 struct Node {
     // row, col: 1-based b/c of head node (only internally)
@@ -16,6 +17,10 @@ struct Node {
 }
 
 /// A sparse matrix representation of an exact cover problem used for DLX algorithm.
+/// 
+/// A clone has its own copy of the whole search state,
+/// so it can be modified or solved independently.
+#[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(test, derive(Debug))]
 pub struct Matrix {
     row_cnt: usize,
@@ -64,19 +69,40 @@ impl Matrix {
         mat
     }
 
-    pub fn with_rows(col_cnt: usize, rows: &[&[usize]]) -> Matrix {
+    /// Creates a matrix with `col_cnt` columns and the given rows.
+    /// 
+    /// It returns an error if any row is invalid (see [`add_row`](Matrix::add_row)).
+    pub fn with_rows(col_cnt: usize, rows: &[&[usize]]) -> Result<Matrix, MatrixError> {
         let mut mat = Matrix::new(col_cnt);
-        for row in rows { mat.add_row(row) }
-        mat
+        for row in rows { mat.add_row(row)? }
+        Ok(mat)
+    }
+
+    /// Parses a matrix exported by [`to_sparse_str`](Matrix::to_sparse_str).
+    pub fn from_sparse_str(s: &str) -> Result<Matrix, ParseMatrixError> {
+        let sparse = SparseMatrix::parse(s, "xc")?;
+        let mut mat = Matrix::new(sparse.col_cnt);
+        for row in &sparse.rows {
+            let row: Vec<_> = row.iter().map(|&(col, _)| col).collect();
+            mat.add_row(&row).unwrap(); // columns are checked while parsing
+        }
+        Ok(mat)
     }
 
-    pub fn add_row(&mut self, row: &[usize]) {
+    /// Adds a row that consists of the columns `row` (1-based).
+    /// 
+    /// It returns an error without changing the matrix
+    /// if the row contains a column out of range.
+    pub fn add_row(&mut self, row: &[usize]) -> Result<(), MatrixError> {
+        if let Some(&col) = row.iter().find(|&&col| col == 0 || col > self.col_cnt) {
+            return Err(MatrixError::ColumnOutOfRange { col, col_cnt: self.col_cnt });
+        }
+
         self.row_cnt += 1;
         let row_num = self.row_cnt;
         let mut left_node = 0;
 
         for &col_num in row {
-            assert!(1 <= col_num && col_num <= self.col_cnt); // TODO: write proper validation logic
             let node = self.create_node(row_num, col_num);
 
             self.insert_down(self.pool[col_num].up, node);
@@ -85,6 +111,7 @@ impl Matrix {
             self.col_size[col_num] += 1;
             left_node = node;
         }
+        Ok(())
     }
 }
 
@@ -119,8 +146,8 @@ impl Matrix {
 
         // MRV (minimum remaining values) heuristic
         // Choose a column with minimal branching factor
-        let (col, size) = self.choose_best_col();
-        if size == 0 { return; } // Dead end
+        let col = self.choose_best_col();
+        if self.col_size[col] == 0 { return; } // Dead end
         
         // Select a row to cover the selected column
         self.cover_col(col);
@@ -165,8 +192,8 @@ impl Matrix {
 
                     // MRV (minimum remaining values) heuristic
                     // Choose a column with minimal branching factor
-                    let (col, size) = self.choose_best_col();
-                    if size == 0 { continue; } // Dead end
+                    let col = self.choose_best_col();
+                    if self.col_size[col] == 0 { continue; } // Dead end
 
                     // Select a row to cover the selected column
                     self.cover_col(col);
@@ -211,7 +238,56 @@ impl Matrix {
             }
         }
 
-        callback.on_finish()
+        callback.on_finish(self)
+    }
+}
+
+// Accessors for callbacks
+impl Matrix {
+    /// Returns the number of columns.
+    pub fn col_count(&self) -> usize { self.col_cnt }
+    /// Returns the number of rows.
+    pub fn row_count(&self) -> usize { self.row_cnt }
+    /// Returns the number of remaining rows in column `col` (1-based) in the current search state.
+    pub fn column_size(&self, col: usize) -> usize { self.col_size[col] }
+
+    /// Returns the rows (1-based) selected in the current partial solution.
+    pub fn partial_solution(&self) -> &[usize] { &self.partial_sol }
+    /// Returns the number of columns chosen so far, i.e. the depth in the search tree.
+    pub fn current_depth(&self) -> usize { self.col_stack.len() }
+}
+
+// Methods for exporting Matrix
+impl Matrix {
+    /// Exports the rows in a DIMACS-like sparse text format,
+    /// which can be parsed back by [`from_sparse_str`](Matrix::from_sparse_str).
+    /// 
+    /// The first line `p xc <col_cnt> <row_cnt>` is followed by a line for each row,
+    /// listing its columns (1-based) terminated by `0`.
+    pub fn to_sparse_str(&self) -> String {
+        let mut lines = vec![format!("p xc {} {}", self.col_cnt, self.row_cnt)];
+        lines.extend(self.rows().iter().map(|row| {
+            SparseMatrix::row_line(&row.iter().map(|&col| (col, 0)).collect::<Vec<_>>())
+        }));
+        lines.join("\n") + "\n"
+    }
+
+    /// Exports the rows as lines of `0` and `1`, where `1` represents the columns in the row.
+    pub fn to_dense_string(&self) -> String {
+        self.rows().iter()
+            .map(|row| {
+                let mut line = vec!['0'; self.col_cnt];
+                for &c in row { line[c - 1] = '1'; }
+                line.into_iter().chain(['\n']).collect::<String>()
+            })
+            .collect()
+    }
+
+    /// Returns the columns of each row, in the order they were given.
+    fn rows(&self) -> Vec<Vec<usize>> {
+        let mut rows = vec![vec![]; self.row_cnt];
+        for node in &self.pool[self.col_cnt + 1..] { rows[node.row - 1].push(node.col); }
+        rows
     }
 }
 
@@ -314,25 +390,98 @@ impl Matrix {
         }
     }
 
+    /// Returns the column with the lowest `col_size`. (MRV Heuristic).
+    /// It returns `HEAD` if there are no columns left, whose size is always 0.
     #[inline]
-    fn choose_best_col(&self) -> (usize, usize) {
+    fn choose_best_col(&self) -> usize {
         let mut col = self.pool[Matrix::HEAD].right;
-        let mut size = self.col_size[col];
         
         let mut j = col;
         while j != Matrix::HEAD {
-            if self.col_size[j] < size {
+            if self.col_size[j] < self.col_size[col] {
                 col = j;
-                size = self.col_size[j];
             }
             j = self.pool[j].right;
         }
-        (col, size)
+        col
     }
 }
 
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use crate::dlx::callback::SolutionCallback;
+
+    #[test]
+    fn cloned_matrix_is_independent() {
+        let solve = |mat: &mut Matrix| {
+            let mut callback = SolutionCallback::default();
+            mat.solve(&mut callback);
+            callback.solutions
+        };
+
+        let mut mat = Matrix::with_rows(3, &[&[1, 2, 3], &[1], &[2]]).unwrap();
+        let mut copy = mat.clone();
+        for m in [&mut mat, &mut copy] {
+            m.add_row(&[3]).unwrap();
+            m.add_row(&[1, 2]).unwrap();
+            m.add_row(&[2, 3]).unwrap();
+        }
+        let mut modified = mat.clone();
+        modified.add_row(&[1, 3]).unwrap();
+
+        let solutions = solve(&mut mat);
+        assert_eq!(solutions, vec![vec![1], vec![2, 3, 4], vec![2, 6], vec![5, 4]]);
+        assert_eq!(solve(&mut copy), solutions);
+        assert_eq!(solve(&mut modified).len(), 5);
+    }
+
+    #[test]
+    fn matrix_reports_its_dimensions() {
+        let mut mat = Matrix::with_rows(4, &[&[1, 2], &[1, 3], &[2, 3, 4]]).unwrap();
+        assert_eq!((mat.col_count(), mat.row_count()), (4, 3));
+        assert_eq!((1..=4).map(|c| mat.column_size(c)).collect::<Vec<_>>(), vec![2, 2, 2, 1]);
+
+        mat.cover_col(1);
+        assert_eq!((1..=4).map(|c| mat.column_size(c)).collect::<Vec<_>>(), vec![2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn best_column_has_fewest_rows() {
+        let mut mat = Matrix::with_rows(4, &[&[1, 2], &[1, 3], &[2, 3, 4], &[1, 4]]).unwrap();
+        assert_eq!(mat.choose_best_col(), 2);
+        assert_eq!(mat.col_size[2], 2);
+
+        mat.cover_col(2);
+        assert_eq!(mat.choose_best_col(), 3);
+        assert_eq!(mat.col_size[3], 1);
+
+        let empty = Matrix::new(0);
+        assert_eq!(empty.choose_best_col(), Matrix::HEAD);
+    }
+
+    #[test]
+    fn out_of_range_column_is_rejected() {
+        let mut mat = Matrix::with_rows(3, &[&[1, 2], &[3]]).unwrap();
+        assert_eq!(mat.add_row(&[2, 4]), Err(MatrixError::ColumnOutOfRange { col: 4, col_cnt: 3 }));
+        assert_eq!(mat.add_row(&[0]), Err(MatrixError::ColumnOutOfRange { col: 0, col_cnt: 3 }));
+        assert_eq!(mat.row_cnt, 2);
+        assert_eq!(mat.col_size, vec![0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn matrix_can_be_exported() {
+        let mat = Matrix::with_rows(4, &[&[1, 2], &[], &[4, 2, 3]]).unwrap();
+        assert_eq!(mat.to_dense_string(), "1100\n0000\n0111\n");
+
+        let sparse = mat.to_sparse_str();
+        assert_eq!(sparse, "p xc 4 3\n1 2 0\n0\n4 2 3 0\n");
+        assert_eq!(Matrix::from_sparse_str(&sparse), Ok(mat));
+
+        let err = |line| Err(ParseMatrixError { line });
+        assert_eq!(Matrix::from_sparse_str("c comment\np xc 2 1\n\n3 0\n"), err(4));
+        assert_eq!(Matrix::from_sparse_str("p xc 2 2\n1 0\n"), err(3));
+        assert_eq!(Matrix::from_sparse_str("p xcm 2 0\nm 1 0 1\n"), err(1));
+    }
 }
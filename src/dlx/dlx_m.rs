@@ -2,10 +2,14 @@
 //! 
 //! This module extends [`dlx`](crate::dlx) module to handle multiplicity.
 
+use indexmap::IndexSet;
+use crate::dlx::{MatrixError, ParseMatrixError, SparseMatrix};
 use crate::dlx::callback::{Callback};
+use crate::dlx::chooser::{ColumnChooser, Mrv};
+use crate::problem::{Problem, ProblemError, Subset, Value};
 
 /// A single node of [`Matrix`].
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 struct Node {
     // row, col: 1-based b/c of head node (only internally)
     row: usize,
@@ -14,26 +18,83 @@ struct Node {
     right: usize,
     up: usize,
     down: usize,
+    color: usize, // 0 if uncolored, or `Matrix::PURIFIED`
+}
+
+/// Statistics of a search, accumulated while solving a [`Matrix`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStats {
+    /// The number of solutions found.
+    pub solutions: usize,
+    /// The number of search tree nodes visited.
+    pub iterations: usize,
+    /// The number of times the search returned from a branch.
+    pub backtracks: usize,
+    /// The maximum number of columns chosen at once, i.e. the depth of the search tree.
+    pub max_depth: usize,
+}
+
+/// A transition of the search, reported by [`Matrix::step`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchStep {
+    /// Column `col` (1-based) is chosen to branch on.
+    ColumnChosen(usize),
+    /// Row `row` (1-based) is added to the partial solution.
+    RowSelected(usize),
+    /// Row `row` (1-based) is removed from the partial solution.
+    Backtracked(usize),
+    /// The partial solution is a solution.
+    SolutionFound(Vec<usize>),
 }
 
 /// A sparse matrix representation of an exact cover problem used for DLX algorithm.
+/// 
+/// A clone has its own copy of the whole search state,
+/// so it can be solved independently, e.g. in another thread.
+/// 
+/// # Colors
+/// 
+/// A secondary column (see [`set_secondary`](Matrix::set_secondary)) is never chosen to branch on.
+/// It can be covered by at most one row without a color on it,
+/// or by any number of rows that agree on its color (see [`add_colored_row`](Matrix::add_colored_row)),
+/// as in Knuth's exact cover with colors (XCC).
+#[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(test, derive(Debug))]
 pub struct Matrix {
     row_cnt: usize,
     col_cnt: usize,
     pool: Vec<Node>, // head: 0, columns: 1..=col_cnt
     col_size: Vec<usize>,
+    row_head: Vec<usize>, // first node of each row, or 0 if the row is empty
+    disabled: Vec<bool>,
     
     // column multiplicity range
     min: Vec<usize>,
     max: Vec<usize>,
     weight: Vec<usize>,
 
+    // secondary columns with colors
+    secondary: Vec<bool>,
+    col_color: Vec<usize>, // the color that each column is purified to, or 0
+    col_name: Vec<Option<String>>,
+
+    // row costs for branch and bound
+    row_cost: Vec<usize>,
+    cost: usize, // total cost of the partial solution
+    cost_bound: Option<usize>, // prune partial solutions costing at least this much
+    max_depth: Option<usize>, // maximum number of rows in a partial solution
+
     partial_sol: Vec<usize>,
-    _col_stack: Vec<usize>,
-    _row_stack: Vec<usize>,
-    _task_stack: Vec<usize>,
+    col_stack: Vec<usize>,
+    row_stack: Vec<usize>,
+    first_stack: Vec<usize>, // first row of each chosen column, to untweak rows later
+    branch_stack: Vec<(usize, usize)>, // (explored, total) branches of each chosen column
+    task_stack: Vec<usize>,
     abort_requested: bool,
+    prune_requested: bool, // skip the current node, requested in `on_iteration`
+    partition: (usize, usize), // (index, count) of the top-level branches to explore
+
+    stats: SearchStats,
 }
 
 impl Default for Matrix {
@@ -43,16 +104,33 @@ impl Default for Matrix {
             col_cnt: 0,
             pool: vec![Node::default()],
             col_size: vec![0],
+            row_head: vec![0],
+            disabled: vec![false],
 
             min: vec![0],
             max: vec![0],
             weight: vec![0],
 
+            secondary: vec![false],
+            col_color: vec![0],
+            col_name: vec![None],
+
+            row_cost: vec![0],
+            cost: 0,
+            cost_bound: None,
+            max_depth: None,
+
             partial_sol: vec![],
-            _col_stack: vec![],
-            _row_stack: vec![],
-            _task_stack: vec![],
+            col_stack: vec![],
+            row_stack: vec![],
+            first_stack: vec![],
+            branch_stack: vec![],
+            task_stack: vec![],
             abort_requested: false,
+            prune_requested: false,
+            partition: (0, 1),
+
+            stats: SearchStats::default(),
         }
     }
 }
@@ -60,6 +138,7 @@ impl Default for Matrix {
 // Methods for initializing Matrix
 impl Matrix {
     const HEAD: usize = 0;
+    const PURIFIED: usize = usize::MAX; // the color of nodes agreeing with their purified column
 
     pub fn new(col_cnt: usize) -> Matrix {
         // Set multiplicity to [1, 1] by default
@@ -72,6 +151,9 @@ impl Matrix {
             min: ones.clone(),
             max: ones.clone(),
             weight: vec![0; col_cnt + 1],
+            secondary: vec![false; col_cnt + 1],
+            col_color: vec![0; col_cnt + 1],
+            col_name: vec![None; col_cnt + 1],
             ..Matrix::default()
         };
         for col_num in 1..=col_cnt {
@@ -81,20 +163,56 @@ impl Matrix {
         mat
     }
 
-    pub fn with_rows(col_cnt: usize, rows: &[&[usize]]) -> Matrix {
+    /// Creates a matrix with `col_cnt` columns, reserving room for `expected_nodes` nodes in the rows,
+    /// i.e. the total length of the rows to be added.
+    pub fn with_capacity(col_cnt: usize, expected_nodes: usize) -> Matrix {
         let mut mat = Matrix::new(col_cnt);
-        for row in rows { mat.add_row(row) }
+        mat.pool.reserve(expected_nodes);
         mat
     }
 
-    pub fn add_row(&mut self, row: &[usize]) {
+    /// Creates a matrix with `col_cnt` columns and the given rows.
+    /// 
+    /// It returns an error if any row is invalid (see [`add_row`](Matrix::add_row)).
+    pub fn with_rows(col_cnt: usize, rows: &[&[usize]]) -> Result<Matrix, MatrixError> {
+        let mut mat = Matrix::new(col_cnt);
+        for row in rows { mat.add_row(row)? }
+        Ok(mat)
+    }
+
+    /// Adds a row that consists of the columns `row` (1-based).
+    /// 
+    /// It returns an error without changing the matrix
+    /// if the row contains a column out of range.
+    pub fn add_row(&mut self, row: &[usize]) -> Result<(), MatrixError> {
+        let row: Vec<_> = row.iter().map(|&col| (col, 0)).collect();
+        self.add_colored_row(&row)
+    }
+
+    /// Adds a row that consists of the columns (1-based) with their colors.
+    /// 
+    /// Color 0 means no color, and the other colors must be less than `usize::MAX`.
+    /// It returns an error without changing the matrix if the row contains a column out of range,
+    /// or a color on a column which is not secondary (see [`set_secondary`](Matrix::set_secondary)).
+    pub fn add_colored_row(&mut self, row: &[(usize, usize)]) -> Result<(), MatrixError> {
+        if let Some(&(col, _)) = row.iter().find(|&&(col, _)| col == 0 || col > self.col_cnt) {
+            return Err(MatrixError::ColumnOutOfRange { col, col_cnt: self.col_cnt });
+        }
+        if let Some(&(col, _)) = row.iter().find(|&&(col, color)| color != 0 && !self.secondary[col]) {
+            return Err(MatrixError::ColoredPrimaryColumn { col });
+        }
+        assert!(row.iter().all(|&(_, color)| color != Matrix::PURIFIED), "Color must be less than usize::MAX");
+
         self.row_cnt += 1;
         let row_num = self.row_cnt;
         let mut left_node = 0;
+        self.row_head.push(if row.is_empty() { 0 } else { self.pool.len() });
+        self.disabled.push(false);
+        self.row_cost.push(1);
 
-        for &col_num in row {
-            assert!(1 <= col_num && col_num <= self.col_cnt); // TODO: write proper validation logic
+        for &(col_num, color) in row {
             let node = self.create_node(row_num, col_num);
+            self.pool[node].color = color;
 
             self.insert_down(self.pool[col_num].up, node);
             if left_node != 0 { self.insert_right(left_node, node); }
@@ -102,12 +220,247 @@ impl Matrix {
             self.col_size[col_num] += 1;
             left_node = node;
         }
+        Ok(())
     }
 
+    /// Parses a matrix exported by [`to_sparse_str`](Matrix::to_sparse_str).
+    pub fn from_sparse_str(s: &str) -> Result<Matrix, ParseMatrixError> {
+        let sparse = SparseMatrix::parse(s, "xcm")?;
+        let mut mat = Matrix::new(sparse.col_cnt);
+        for (col, min, max) in sparse.multiplicities { mat.set_multiplicity(col, min, max); }
+        for col in sparse.secondary { mat.set_secondary(col); }
+        for (col, name) in sparse.names { mat.set_col_name(col, name); }
+        for row in &sparse.rows { mat.add_colored_row(row).unwrap(); } // columns and colors are checked while parsing
+        Ok(mat)
+    }
+
+    /// Generates a matrix of a problem, after validating it with [`Problem::validate`],
+    /// to solve it with the low-level API, e.g. with a custom [`Callback`].
+    /// 
+    /// It also returns the subset names, where `names[row - 1]` is the subset of row `row` (1-based).
+    /// The columns correspond to the constraints in the insertion order,
+    /// and the forced subsets are already selected, as the solver does.
+    pub fn from_problem<N: Value, E: Value>(problem: &Problem<N, E>) -> Result<(Matrix, Vec<N>), ProblemError> {
+        problem.validate()?;
+        let mut mat = Matrix::generate_unforced(problem);
+        mat.force_subsets(problem);
+        Ok((mat, problem.subset_names().cloned().collect()))
+    }
+
+    /// Generates a matrix of a problem without selecting the forced subsets.
+    /// The problem must be already validated.
+    pub(crate) fn generate_unforced<N: Value, E: Value>(problem: &Problem<N, E>) -> Matrix {
+        let constraints = problem.constraints();
+        let names = problem.subsets().keys();
+        let node_cnt = problem.subsets().values().map(|subset| subset.len()).sum();
+        let mut mat = Matrix::with_capacity(constraints.len(), node_cnt);
+
+        for (e, &(min, max)) in constraints {
+            mat.set_multiplicity(constraints.get_index_of(e).unwrap() + 1, min, max);
+        }
+        for e in problem.secondary_constraints() {
+            mat.set_secondary(constraints.get_index_of(e).unwrap() + 1);
+        }
+
+        // Matrix colors are 1-based indices of the colors used in the problem
+        let mut colors = IndexSet::new();
+        for name in names {
+            let row: Subset<_> = problem.subsets()[name].iter()
+                .map(|e| {
+                    let color = match problem.color(name, e) {
+                        Some(color) => colors.insert_full(color).0 + 1,
+                        None => 0,
+                    };
+                    (constraints.get_index_of(e).unwrap() + 1, color)
+                })
+                .collect();
+            mat.add_colored_row(&row).unwrap(); // the problem is already validated
+        }
+        mat
+    }
+
+    /// Selects the rows of the forced subsets. The problem must be already validated.
+    pub(crate) fn force_subsets<N: Value, E: Value>(&mut self, problem: &Problem<N, E>) {
+        for name in problem.forced_subsets() {
+            let row = problem.subsets().get_index_of(name).unwrap() + 1;
+            self.force_row(row).unwrap();
+        }
+    }
+
+    /// Sets the multiplicity range of column `col` (1-based),
+    /// i.e. it has to be covered at least `min` times and at most `max` times.
+    /// 
+    /// It panics if `min` is greater than `max`.
     pub fn set_multiplicity(&mut self, col: usize, min: usize, max: usize) {
+        assert!(min <= max, "Column {} has a minimum multiplicity {} greater than its maximum {}", col, min, max);
         self.min[col] = min;
         self.max[col] = max;
     }
+
+    /// Makes column `col` (1-based) secondary, so that it can have colors (see [Colors](Matrix#colors)).
+    /// 
+    /// A secondary column is covered at most once without colors,
+    /// and it is never chosen to branch on, so the search does not require it to be covered.
+    /// Its multiplicity range is ignored. Do not call it in the middle of a search.
+    pub fn set_secondary(&mut self, col: usize) {
+        if self.secondary[col] { return; }
+        self.secondary[col] = true;
+        self.set_multiplicity(col, 0, 1);
+
+        // Remove it from the column list, and link it to itself
+        // so that covering it does not change the column list.
+        let Node { left, right, .. } = self.pool[col];
+        self.pool[left].right = right;
+        self.pool[right].left = left;
+        self.pool[col].left = col;
+        self.pool[col].right = col;
+    }
+
+    /// Names column `col` (1-based), e.g. after the constraint it represents.
+    /// 
+    /// The names are only used to make the exports human-readable,
+    /// and do not affect the search.
+    /// It panics if `name` is empty or contains whitespace,
+    /// which separates the names in the exports.
+    pub fn set_col_name(&mut self, col: usize, name: impl Into<String>) {
+        let name = name.into();
+        assert!(!name.is_empty() && !name.contains(char::is_whitespace), "Invalid column name {:?}", name);
+        self.col_name[col] = Some(name);
+    }
+
+    /// Sets the cost of row `row` (1-based). Each row costs 1 by default.
+    pub fn set_row_cost(&mut self, row: usize, cost: usize) {
+        if self.partial_sol.contains(&row) { // forced row
+            self.cost = self.cost - self.row_cost[row] + cost;
+        }
+        self.row_cost[row] = cost;
+    }
+
+    /// Sets an upper bound of the solution cost.
+    /// 
+    /// Partial solutions whose total row cost is not less than `bound` are pruned,
+    /// so the following solutions always cost less than `bound`.
+    /// Callbacks can tighten the bound in the middle of a search (branch and bound).
+    pub fn set_cost_bound(&mut self, bound: Option<usize>) {
+        self.cost_bound = bound;
+    }
+
+    /// Limits the number of rows in a partial solution.
+    /// 
+    /// The search backtracks instead of selecting a row beyond the limit,
+    /// so only the solutions consisting of at most `max_depth` rows are found.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    /// Returns the total row cost of the current partial solution.
+    pub fn cost(&self) -> usize { self.cost }
+
+    /// Returns the statistics of the search so far.
+    /// They are kept when the search is aborted and resumed.
+    pub fn stats(&self) -> SearchStats { self.stats }
+
+    /// Restricts the search to the top-level branches
+    /// whose indices are congruent to `index` modulo `count`.
+    /// 
+    /// Solving `count` clones of a matrix with each `index` in `0..count`
+    /// finds every solution exactly once in total, so they can be solved in parallel.
+    /// Do not call it in the middle of a search.
+    pub fn set_partition(&mut self, index: usize, count: usize) {
+        assert!(index < count, "Partition index must be less than the count");
+        self.partition = (index, count);
+    }
+
+    /// Shuffles the order in which the rows of each column are tried.
+    /// 
+    /// Every row is still tried exactly once, so the set of solutions does not change,
+    /// but they are found in a different order. Do not call it in the middle of a search.
+    #[cfg(feature = "rand")]
+    pub fn shuffle_rows(&mut self, rng: &mut impl rand::Rng) {
+        use rand::seq::SliceRandom;
+        assert!(self.task_stack.is_empty(), "Rows cannot be shuffled in the middle of a search");
+        assert!(self.partial_sol.is_empty(), "Rows cannot be shuffled after forcing rows");
+
+        // Disabled rows are hidden from the columns, so relink them as well
+        let disabled: Vec<_> = (1..=self.row_cnt).filter(|&row| self.disabled[row]).collect();
+        for &row in &disabled { self.enable_row(row); }
+
+        for c in 1..=self.col_cnt {
+            let mut nodes = vec![];
+            let mut r = self.pool[c].down;
+            while r != c {
+                nodes.push(r);
+                r = self.pool[r].down;
+            }
+            nodes.shuffle(rng);
+
+            let mut prev = c;
+            for r in nodes.into_iter().chain([c]) {
+                self.pool[prev].down = r;
+                self.pool[r].up = prev;
+                prev = r;
+            }
+        }
+
+        for &row in &disabled { self.disable_row(row); }
+    }
+
+    /// Selects row `row` (1-based) in advance, so that every solution contains it.
+    /// 
+    /// The row is added to the partial solution and stays selected across backtracking,
+    /// so the search only finds the completions consistent with the forced rows.
+    /// It returns an error without changing the matrix if the row is disabled,
+    /// or if selecting it exceeds the maximum multiplicity of any of its columns.
+    /// Do not call it in the middle of a search.
+    pub fn force_row(&mut self, row: usize) -> Result<(), MatrixError> {
+        let mut nodes = vec![];
+        let r = self.row_head[row];
+        if r != 0 {
+            let mut j = r;
+            loop {
+                nodes.push(j);
+                j = self.pool[j].right;
+                if j == r { break; }
+            }
+        }
+        if self.disabled[row] || nodes.iter().any(|&j| self.node_conflicts(j)) {
+            return Err(MatrixError::ConflictingRow { row });
+        }
+
+        // Remove the row itself from the matrix, and then select each node like `select_row`
+        self.disable_row(row);
+        for j in nodes { self.select_node(j); }
+        self.partial_sol.push(row);
+        self.cost += self.row_cost[row];
+        Ok(())
+    }
+
+    /// Disables row `row` (1-based) so that the following searches never select it.
+    /// 
+    /// Unlike covering a column, it only hides the row itself.
+    /// It can be reverted by [`enable_row`](Matrix::enable_row).
+    /// Do not call it in the middle of a search.
+    pub fn disable_row(&mut self, row: usize) {
+        if self.disabled[row] { return; }
+        self.disabled[row] = true;
+
+        let r = self.row_head[row];
+        if r == 0 { return; }
+        self.hide_node(r);
+        self.hide_row(r);
+    }
+
+    /// Enables row `row` (1-based) disabled by [`disable_row`](Matrix::disable_row).
+    /// It does nothing for forced rows.
+    pub fn enable_row(&mut self, row: usize) {
+        if !self.disabled[row] || self.partial_sol.contains(&row) { return; }
+        self.disabled[row] = false;
+
+        let r = self.row_head[row];
+        if r == 0 { return; }
+        self.unhide_row(r);
+        self.unhide_node(r);
+    }
 }
 
 // Main algorithm (dancing links)
@@ -115,9 +468,34 @@ impl Matrix {
     pub fn solve(
         &mut self,
         callback: &mut impl Callback<Matrix>,
+    ) {
+        self.solve_with(callback, &Mrv);
+    }
+
+    /// Solves the matrix choosing columns with the given heuristic instead of [`Mrv`].
+    /// 
+    /// When resuming an aborted search, use the same heuristic as before,
+    /// otherwise some solutions may be missed or found twice.
+    pub fn solve_with(
+        &mut self,
+        callback: &mut impl Callback<Matrix>,
+        chooser: &impl ColumnChooser<Matrix>,
     ) {
         self.abort_requested = false;
-        self._recursive_solve(callback);
+        self.iterative_solve(callback, chooser, false);
+    }
+
+    /// Advances the search until the next transition, and returns it.
+    /// 
+    /// It returns `None` once the search has finished, even if called again.
+    /// The search can be continued by [`solve`](Matrix::solve) at any step,
+    /// but note that `solve` starts a new search after the search has finished.
+    pub fn step(&mut self) -> Option<SearchStep> {
+        let finished = self.task_stack.is_empty() && self.stats.iterations > 0;
+        if finished { return None; }
+
+        self.abort_requested = false;
+        self.iterative_solve(&mut (), &Mrv, true)
     }
 
     /// A recursive DLX algorithm.
@@ -151,14 +529,14 @@ impl Matrix {
         //
         // After selecting a row in the previous recursion level,
         // some columns can become unfulfillable. (See `select_row` to check the details.)
-        // So `choose_best_col` prioritizes unfulfillable columns for early return.
+        // So `Mrv` prioritizes unfulfillable columns for early return.
         //
         // Also, it deprioritizes already-fulfilled columns as well,
         // because it is more effective to increase the number of fulfilled columns directly.
         // 
         // We don't have any fulfilled columns remaining in the matrix,
         // because every column is covered as soon as it is fulfilled.
-        let c = self.choose_best_col(); // TODO-A: modify find best column logic
+        let c = Mrv.choose(self); // TODO-A: modify find best column logic
         if c == Matrix::HEAD || !self.col_fulfillable(c) { return; }
         
         // [COVER-FULL] If column c becomes full after selecting any row, cover it in advance.
@@ -185,6 +563,10 @@ impl Matrix {
             self.partial_sol.push(self.pool[r].row);
             
             // If column c becomes unfulfillable after selecting a row, don't recurse.
+            // Note that it checks the current column c, not the row node r:
+            // selecting r may cover other columns and hide the remaining rows of c,
+            // so c can no longer reach its minimum multiplicity.
+            // Other columns are checked when they are chosen in deeper levels.
             // TODO: (optimization) Compare performance with/without the condition below.
             if self.col_fulfillable(c) {
                 self._recursive_solve(callback);
@@ -222,76 +604,350 @@ impl Matrix {
         }
     }
 
-    // // TODO: Fix it to match the new recursive algorithm.
-    // /// An iterative DLX algorithm.
-    // fn _iterative_solve(&mut self, callback: &mut impl Callback) {
-    //     self.task_stack.push(1);
-
-    //     while !self.task_stack.is_empty() {
-    //         match self.task_stack.pop().unwrap() {
-    //             1 => {
-    //                 // Handle callbacks
-    //                 if self.pool[Matrix::HEAD].right == Matrix::HEAD {
-    //                     callback.on_solution(self.partial_sol.clone(), self);
-    //                 }
-
-    //                 callback.on_iteration(self);
-
-    //                 if self.abort_requested {
-    //                     callback.on_abort(self);
-    //                     return
-    //                 }
-
-    //                 // MRV (minimum remaining values) heuristic
-    //                 // Choose a column with minimal branching factor
-    //                 let (col, size) = self.choose_best_col();
-    //                 if size == 0 { continue; } // Dead end
-
-    //                 // Select a row to cover the selected column
-    //                 self.cover_col(col);
-
-    //                 let r = self.pool[col].down;
-    //                 // End of chunk
-    //                 self.col_stack.push(col);
-    //                 self.row_stack.push(r);
-    //                 self.task_stack.push(2);
-    //             }
-    //             2 => {
-    //                 // Restore variables
-    //                 let r = *self.row_stack.last().unwrap();
-                    
-    //                 self.select_row(r);
-    //                 self.partial_sol.push(self.pool[r].row);
-                    
-    //                 // End of chunk
-    //                 self.task_stack.push(3);
-    //                 self.task_stack.push(1);
-    //             }
-    //             3 => {
-    //                 // Restore variables
-    //                 let col = *self.col_stack.last().unwrap();
-    //                 let mut r = self.row_stack.pop().unwrap();
-                    
-    //                 self.unselect_row(r);
-    //                 self.partial_sol.pop();
-                    
-    //                 r = self.pool[r].down;
-    //                 // End of chunk
-    //                 if r == col {
-    //                     // Out of while loop
-    //                     self.uncover_col(col);
-    //                     self.col_stack.pop();
-    //                 } else {
-    //                     self.row_stack.push(r);
-    //                     self.task_stack.push(2);
-    //                 }
-    //             }
-    //             _ => { panic!("Unexpected implementation error"); }
-    //         }
-    //     }
-
-    //     callback.on_finish()
-    // }
+    /// An iterative DLX algorithm with multiplicity.
+    /// 
+    /// It follows the same steps as [`_recursive_solve`], but keeps the whole search state
+    /// in the matrix itself, so that an aborted search can be resumed later
+    /// by calling [`solve`](Matrix::solve) again.
+    /// 
+    /// If `stepping` is set, it returns right after the first transition of the search.
+    fn iterative_solve(
+        &mut self,
+        callback: &mut impl Callback<Matrix>,
+        chooser: &impl ColumnChooser<Matrix>,
+        stepping: bool,
+    ) -> Option<SearchStep> {
+        // Start from the root unless we are resuming an aborted search
+        if self.task_stack.is_empty() {
+            self.task_stack.push(1);
+        }
+
+        while let Some(task) = self.task_stack.pop() {
+            match task {
+                1 => {
+                    // Handle callbacks
+                    callback.on_iteration(self);
+
+                    if self.abort_requested {
+                        // Revisit this node when resumed
+                        self.task_stack.push(1);
+                        callback.on_abort(self);
+                        return None
+                    }
+
+                    self.stats.iterations += 1;
+                    self.stats.max_depth = self.stats.max_depth.max(self.col_stack.len());
+
+                    // [PRUNE] requested by the callback
+                    if std::mem::take(&mut self.prune_requested) { continue; }
+
+                    // [BOUND] Row costs are non-negative, so the cost never decreases deeper.
+                    if self.cost_bound.is_some_and(|bound| self.cost >= bound) { continue; }
+
+                    // The root can only be a solution of the first partition
+                    let in_partition = self.partition.0 == 0 || !self.branch_stack.is_empty();
+                    if self.pool[Matrix::HEAD].right == Matrix::HEAD && in_partition {
+                        self.stats.solutions += 1;
+                        callback.on_solution(self.partial_sol.clone(), self);
+
+                        // A solution is a leaf node, so there is nothing to revisit
+                        if self.abort_requested {
+                            callback.on_abort(self);
+                            return None
+                        }
+                        // There are no columns left, so the rest of the task can be skipped
+                        if stepping { return Some(SearchStep::SolutionFound(self.partial_sol.clone())); }
+                    }
+
+                    // [CHOOSE-COLUMN]
+                    let c = chooser.choose(self);
+                    if c == Matrix::HEAD { continue; }
+                    if !self.col_fulfillable(c) {
+                        callback.on_dead_end(c, self);
+                        continue;
+                    }
+
+                    // Each row is a branch, and so is NO-SELECT if c is already fulfilled
+                    let branch_cnt = self.col_size[c] + self.col_fulfilled(c) as usize;
+
+                    // [COVER-FULL]
+                    self.weight[c] += 1; // will select a row
+                    if self.col_full(c) {
+                        self.cover_col(c);
+                    }
+
+                    let first = self.pool[c].down;
+                    // End of chunk
+                    self.col_stack.push(c);
+                    self.row_stack.push(first);
+                    self.first_stack.push(first);
+                    self.branch_stack.push((0, branch_cnt));
+                    self.task_stack.push(2);
+                    if stepping { return Some(SearchStep::ColumnChosen(c)); }
+                }
+                2 => {
+                    // Restore variables
+                    let c = *self.col_stack.last().unwrap();
+                    let r = *self.row_stack.last().unwrap();
+
+                    if r != c {
+                        // [TRY-ROWS]
+                        // The weight of column c is not changed in deeper levels after backtracking,
+                        // so column c is covered iff it is full.
+                        if !self.col_full(c) {
+                            // [TWEAK-ROW]
+                            self.tweak_row(r);
+                        }
+
+                        let in_partition = self.branch_in_partition();
+                        let too_deep = self.max_depth.is_some_and(|d| self.partial_sol.len() >= d);
+                        if !in_partition || too_deep {
+                            // Skip the branch, but keep the row tweaked as if it was explored
+                            self.row_stack.pop();
+                            if in_partition {
+                                self.advance_branch(callback); // pruned by the depth limit
+                            } else {
+                                self.branch_stack.last_mut().unwrap().0 += 1;
+                            }
+
+                            // End of chunk
+                            self.row_stack.push(self.pool[r].down);
+                            self.task_stack.push(2);
+                            continue;
+                        }
+
+                        self.select_row(r);
+                        self.partial_sol.push(self.pool[r].row);
+                        self.cost += self.row_cost[self.pool[r].row];
+                        callback.on_branch(c, self.pool[r].row, self);
+
+                        // End of chunk
+                        self.task_stack.push(3);
+                        if self.col_fulfillable(c) {
+                            self.task_stack.push(1);
+                        }
+                        if stepping { return Some(SearchStep::RowSelected(self.pool[r].row)); }
+                    } else {
+                        // Out of while loop
+                        // [NO-SELECT]
+                        let covered = self.col_full(c);
+                        self.weight[c] -= 1;
+
+                        if self.col_fulfilled(c) && self.branch_in_partition() {
+                            let Node { left, right, .. } = self.pool[c];
+                            self.pool[left].right = right;
+                            self.pool[right].left = left;
+
+                            // End of chunk
+                            self.task_stack.push(4);
+                            self.task_stack.push(1);
+                        } else {
+                            self.undo_col(covered);
+                        }
+                    }
+                }
+                3 => {
+                    // Restore variables
+                    let r = self.row_stack.pop().unwrap();
+
+                    self.unselect_row(r);
+                    self.partial_sol.pop();
+                    self.cost -= self.row_cost[self.pool[r].row];
+                    self.stats.backtracks += 1;
+                    callback.on_backtrack(self);
+                    self.advance_branch(callback);
+
+                    // End of chunk
+                    self.row_stack.push(self.pool[r].down);
+                    self.task_stack.push(2);
+                    if stepping { return Some(SearchStep::Backtracked(self.pool[r].row)); }
+                }
+                4 => {
+                    // Restore variables
+                    let c = *self.col_stack.last().unwrap();
+
+                    let Node { left, right, .. } = self.pool[c];
+                    self.pool[left].right = c;
+                    self.pool[right].left = c;
+                    self.stats.backtracks += 1;
+                    self.advance_branch(callback);
+
+                    // The weight was already decreased in the NO-SELECT step
+                    let covered = self.weight[c] + 1 == self.max[c];
+                    self.undo_col(covered);
+                }
+                _ => { panic!("Unexpected implementation error"); }
+            }
+        }
+
+        callback.on_finish(self);
+        None
+    }
+
+    /// Returns whether the current branch should be explored with respect to the partition.
+    fn branch_in_partition(&self) -> bool {
+        let (index, count) = self.partition;
+        self.branch_stack.len() != 1 || self.branch_stack[0].0 % count == index
+    }
+
+    /// Marks the current branch as explored.
+    /// It notifies the callback if the branch was one of the top-level branches.
+    fn advance_branch(&mut self, callback: &mut impl Callback<Matrix>) {
+        let top_level = self.branch_stack.len() == 1;
+        let (explored, total) = self.branch_stack.last_mut().unwrap();
+        *explored += 1;
+
+        if top_level {
+            let (explored, total) = (*explored, *total);
+            callback.on_branch_advanced(explored, total, self);
+        }
+    }
+
+    /// [UNDO] Undoes all modifications made to the current column and pops it from the stacks.
+    fn undo_col(&mut self, covered: bool) {
+        let c = self.col_stack.pop().unwrap();
+        self.row_stack.pop();
+        let first = self.first_stack.pop().unwrap();
+        self.branch_stack.pop();
+
+        if covered {
+            self.uncover_col(c);
+        } else {
+            self.untweak_rows(first);
+        }
+    }
+}
+
+// Accessors for callbacks and column choosers
+impl Matrix {
+    /// Returns the number of columns.
+    pub fn col_count(&self) -> usize { self.col_cnt }
+    /// Returns the number of rows.
+    pub fn row_count(&self) -> usize { self.row_cnt }
+
+    /// Returns the rows (1-based) selected in the current partial solution.
+    pub fn partial_solution(&self) -> &[usize] { &self.partial_sol }
+    /// Returns the number of columns chosen so far, i.e. the depth in the search tree.
+    /// 
+    /// It can be greater than the length of the partial solution,
+    /// because a fulfilled column can be chosen without selecting any row.
+    pub fn current_depth(&self) -> usize { self.col_stack.len() }
+
+    /// Returns an iterator over the remaining columns (1-based) in the current search state.
+    pub fn columns(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(self.pool[Matrix::HEAD].right), move |&c| Some(self.pool[c].right))
+            .take_while(|&c| c != Matrix::HEAD)
+    }
+
+    /// Returns an estimate of the fraction of the search that has been done, from 0 to 1.
+    /// 
+    /// As in Knuth's estimate, each branch at depth `d` weighs the product of `1 / total`
+    /// over the columns chosen along the current path, where `total` is the number of branches of each column.
+    /// The fully explored branches along the path are summed up with their weights,
+    /// so it never decreases and takes O(depth) time.
+    /// It is 1 once the search has finished.
+    pub fn progress(&self) -> f32 {
+        if self.branch_stack.is_empty() {
+            let finished = self.stats.iterations > 0 && self.task_stack.is_empty();
+            return if finished { 1.0 } else { 0.0 };
+        }
+
+        // Nested from the deepest level, so that a finished level adds up to exactly one branch above
+        let fraction = self.branch_stack.iter().rev()
+            .fold(0.0, |deeper, &(explored, total)| (explored as f64 + deeper) / total.max(1) as f64);
+        fraction as f32
+    }
+
+    /// Returns the remaining columns (1-based) that are not covered as many times as their minimums yet.
+    pub fn uncovered_columns(&self) -> Vec<usize> {
+        self.columns().filter(|&c| self.weight[c] < self.min[c]).collect()
+    }
+
+    /// Returns the remaining rows (1-based) that can still be selected, in increasing order.
+    pub fn remaining_rows(&self) -> Vec<usize> {
+        let mut rows: Vec<_> = self.columns()
+            .flat_map(|c| {
+                std::iter::successors(Some(self.pool[c].down), move |&i| Some(self.pool[i].down))
+                    .take_while(move |&i| i != c)
+                    .map(|i| self.pool[i].row)
+            })
+            .collect();
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+
+    /// Returns the number of remaining rows in column `col`.
+    pub fn column_size(&self, col: usize) -> usize { self.col_size[col] }
+    /// Returns the first remaining row (1-based) in column `col`, which is tried first when branching on it.
+    pub fn column_first_row(&self, col: usize) -> Option<usize> {
+        let node = self.pool[col].down;
+        (node != col).then(|| self.pool[node].row)
+    }
+    /// Returns the multiplicity range `(min, max)` of column `col`.
+    pub fn column_multiplicity(&self, col: usize) -> (usize, usize) { (self.min[col], self.max[col]) }
+    /// Returns the number of selected rows in column `col`.
+    pub fn column_weight(&self, col: usize) -> usize { self.weight[col] }
+    /// Returns the name of column `col`, if it is named by [`set_col_name`](Matrix::set_col_name).
+    pub fn column_name(&self, col: usize) -> Option<&str> { self.col_name[col].as_deref() }
+}
+
+// Methods for exporting Matrix
+impl Matrix {
+    /// Exports the rows and the column multiplicities in a DIMACS-like sparse text format,
+    /// which can be parsed back by [`from_sparse_str`](Matrix::from_sparse_str).
+    /// 
+    /// The first line `p xcm <col_cnt> <row_cnt>` is followed by a line `m <col> <min> <max>`
+    /// for each column, a line `s <col>` for each secondary column,
+    /// and a line `n <col> <name>` for each named column (see [`set_col_name`](Matrix::set_col_name)),
+    /// and then by a line for each row, listing its columns (1-based) terminated by `0`.
+    /// A colored node is written as `<col>:<color>`.
+    /// Row costs, disabled rows, and the search state are not exported.
+    pub fn to_sparse_str(&self) -> String {
+        let mut lines = vec![format!("p xcm {} {}", self.col_cnt, self.row_cnt)];
+        lines.extend((1..=self.col_cnt).map(|c| format!("m {} {} {}", c, self.min[c], self.max[c])));
+        lines.extend((1..=self.col_cnt).filter(|&c| self.secondary[c]).map(|c| format!("s {}", c)));
+        lines.extend((1..=self.col_cnt)
+            .filter_map(|c| Some(format!("n {} {}", c, self.column_name(c)?))));
+        lines.extend(self.rows().iter().map(|row| SparseMatrix::row_line(row)));
+        lines.join("\n") + "\n"
+    }
+
+    /// Exports the rows as lines of `0` and `1`, where `1` represents the columns in the row.
+    /// 
+    /// If any column is named (see [`set_col_name`](Matrix::set_col_name)),
+    /// the rows are preceded by a header line of the column names separated by spaces,
+    /// where the unnamed columns are written as their numbers (1-based).
+    /// Note that the first line is not a row in that case, unlike a matrix without names.
+    pub fn to_dense_string(&self) -> String {
+        let header = self.col_name.iter().any(Option::is_some).then(|| {
+            let names: Vec<_> = (1..=self.col_cnt)
+                .map(|c| self.column_name(c).map_or_else(|| c.to_string(), str::to_string))
+                .collect();
+            names.join(" ") + "\n"
+        });
+        let rows = self.rows().into_iter()
+            .map(|row| {
+                let mut line = vec!['0'; self.col_cnt];
+                for (c, _) in row { line[c - 1] = '1'; }
+                line.into_iter().chain(['\n']).collect::<String>()
+            });
+        header.into_iter().chain(rows).collect()
+    }
+
+    /// Returns the columns of each row with their colors, in the order they were given.
+    fn rows(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut rows = vec![vec![]; self.row_cnt];
+        for node in &self.pool[self.col_cnt + 1..] {
+            // A purified node has the color of its column
+            let color = match node.color {
+                Matrix::PURIFIED => self.col_color[node.col],
+                color => color,
+            };
+            rows[node.row - 1].push((node.col, color));
+        }
+        rows
+    }
 }
 
 // Helper methods
@@ -300,6 +956,13 @@ impl Matrix {
         self.abort_requested = true;
     }
 
+    /// Skips the subtree of the current search node, including the node itself.
+    /// 
+    /// It only takes effect when called from [`Callback::on_iteration`].
+    pub fn prune(&mut self) {
+        self.prune_requested = true;
+    }
+
     fn create_node(&mut self, row: usize, col: usize) -> usize {
         let idx = self.pool.len();
         self.pool.push(Node {
@@ -309,6 +972,7 @@ impl Matrix {
             right: idx,
             up: idx,
             down: idx,
+            color: 0,
         });
         idx
     }
@@ -353,35 +1017,85 @@ impl Matrix {
     fn unselect_row(&mut self, r: usize) {
         let mut j = self.pool[r].left;
         while j != r {
-            self.unselect_node(self.pool[j].col);
+            self.unselect_node(j);
             j = self.pool[j].left;
         }
     }
 
     /// Selects (already hidden) node j.
     /// Subroutine of `select_row`.
+    /// 
+    /// A colored node purifies its column instead of adding the weight,
+    /// unless the column is already purified to the same color.
     #[inline]
     fn select_node(&mut self, j: usize) {
-        let c = self.pool[j].col;
-        self.weight[c] += 1;
-        // If column c is full after selecting node j, cover the column
-        if self.col_full(c) {
-            self.cover_col(c);
+        let Node { col: c, color, .. } = self.pool[j];
+        match color {
+            0 => {
+                self.weight[c] += 1;
+                // If column c is full after selecting node j, cover the column
+                if self.col_full(c) {
+                    self.cover_col(c);
+                }
+            }
+            Matrix::PURIFIED => (),
+            _ => self.purify(j),
         }
     }
 
     /// Unselects node j.
     #[inline]
     fn unselect_node(&mut self, j: usize) {
-        let c = self.pool[j].col;
-        if self.col_full(c) {
-            self.uncover_col(c);
+        let Node { col: c, color, .. } = self.pool[j];
+        match color {
+            0 => {
+                if self.col_full(c) {
+                    self.uncover_col(c);
+                }
+                self.weight[c] -= 1;
+            }
+            Matrix::PURIFIED => (),
+            _ => self.unpurify(j),
         }
-        self.weight[c] -= 1;
     }
     
     // ======== Level 3 ========
 
+    /// Purifies the column of (already hidden) node j to the color of j.
+    /// It hides the rows with other colors, and marks the rows with the same color as purified.
+    #[inline]
+    fn purify(&mut self, j: usize) {
+        let Node { col: c, color, .. } = self.pool[j];
+        self.col_color[c] = color;
+
+        let mut r = self.pool[c].down;
+        while r != c {
+            if self.pool[r].color == color {
+                self.pool[r].color = Matrix::PURIFIED;
+            } else {
+                self.hide_row(r);
+            }
+            r = self.pool[r].down;
+        }
+    }
+
+    /// Unpurifies the column of node j.
+    #[inline]
+    fn unpurify(&mut self, j: usize) {
+        let Node { col: c, color, .. } = self.pool[j];
+        self.col_color[c] = 0;
+
+        let mut r = self.pool[c].up;
+        while r != c {
+            if self.pool[r].color == Matrix::PURIFIED {
+                self.pool[r].color = color;
+            } else {
+                self.unhide_row(r);
+            }
+            r = self.pool[r].up;
+        }
+    }
+
     /// Covers column c by hiding all its rows.
     /// It effectively disables the use of column c at all.
     #[inline]
@@ -444,11 +1158,15 @@ impl Matrix {
     /// Hides row r from other columns by hiding each node j in the row.
     /// It doesn't hide node r from its column,
     /// so call it when the column is covered or you have to manually hide node r.
+    /// 
+    /// Purified nodes are not hidden, because their columns are never searched until unpurified.
     #[inline]
     fn hide_row(&mut self, r: usize) {
         let mut j = self.pool[r].right;
         while j != r {
-            self.hide_node(j);
+            if self.pool[j].color != Matrix::PURIFIED {
+                self.hide_node(j);
+            }
             j = self.pool[j].right;
         }
     }
@@ -458,7 +1176,9 @@ impl Matrix {
     fn unhide_row(&mut self, r: usize) {
         let mut j = self.pool[r].left;
         while j != r {
-            self.unhide_node(j);
+            if self.pool[j].color != Matrix::PURIFIED {
+                self.unhide_node(j);
+            }
             j = self.pool[j].left;
         }
     }
@@ -485,34 +1205,28 @@ impl Matrix {
     
     // ======== Level 0 ========
 
-    /// Returns the column with the lowest `col_size`. (MRV Heuristic).
-    #[inline]
-    fn choose_best_col(&self) -> usize {
-        let mut best_col = self.pool[Matrix::HEAD].right;
-        let mut best_size = self.col_size[best_col];
-        
-        let mut c = best_col;
-        while c != Matrix::HEAD {
-            if self.col_size[c] < best_size {
-                best_col = c;
-                best_size = self.col_size[c];
-            }
-            c = self.pool[c].right;
-        }
-        best_col
-    }
-
     /// Returns whether column c is selected within the multiplicity range.
     #[inline]
     fn col_fulfilled(&self, c: usize) -> bool {
         let Matrix { weight, min, max, .. } = self;
-        return min[c] <= weight[c] && weight[c] <= max[c];
+        min[c] <= weight[c] && weight[c] <= max[c]
     }
 
     /// Returns whether column c is fully selected.
     #[inline]
     fn col_full(&self, c: usize) -> bool {
-        return self.weight[c] == self.max[c];
+        self.weight[c] == self.max[c]
+    }
+
+    /// Returns whether selecting node j conflicts with the selected rows.
+    #[inline]
+    fn node_conflicts(&self, j: usize) -> bool {
+        let Node { col: c, color, .. } = self.pool[j];
+        match color {
+            0 => self.col_full(c) || self.col_color[c] != 0,
+            Matrix::PURIFIED => false,
+            _ => self.col_full(c) || (self.col_color[c] != 0 && self.col_color[c] != color),
+        }
     }
     
     /// Returns whether it is possible to select column c within the multiplicity range.
@@ -520,13 +1234,453 @@ impl Matrix {
     fn col_fulfillable(&self, c: usize) -> bool {
         let Matrix { weight, min, max, col_size, .. } = self;
         if weight[c] > max[c] { return false; }
-        if weight[c] + col_size[c] < min[c] { return false; }
-        return true;
+        weight[c] + col_size[c] >= min[c]
     }
 }
 
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use crate::dlx::callback::SolutionCallback;
+
+    fn readme_matrix() -> Matrix {
+        Matrix::with_rows(3, &[
+            &[1, 2, 3], // A
+            &[1],       // B
+            &[2],       // C
+            &[3],       // D
+            &[1, 2],    // E
+            &[2, 3],    // F
+        ]).unwrap()
+    }
+
+    fn multiplicity_matrix() -> Matrix {
+        // Same as `examples/multiplicity.rs`
+        let mut mat = Matrix::with_rows(3, &[
+            &[1, 2, 3], // A
+            &[2],       // B
+            &[1, 2],    // C
+            &[2, 3],    // D
+        ]).unwrap();
+        mat.set_multiplicity(1, 1, 1);
+        mat.set_multiplicity(2, 1, 2);
+        mat.set_multiplicity(3, 0, 1);
+        mat
+    }
+
+    #[test]
+    fn cloned_matrix_is_independent() {
+        let mut mat = Matrix::with_rows(3, &[&[1, 2, 3], &[2]]).unwrap();
+        let mut copy = mat.clone();
+        for m in [&mut mat, &mut copy] {
+            m.add_row(&[1, 2]).unwrap();
+            m.add_row(&[2, 3]).unwrap();
+            m.set_multiplicity(1, 1, 1);
+            m.set_multiplicity(2, 1, 2);
+            m.set_multiplicity(3, 0, 1);
+        }
+
+        let mut original = SolutionCallback::default();
+        mat.solve(&mut original);
+        let mut cloned = SolutionCallback::default();
+        copy.solve(&mut cloned);
+
+        let mut expected = SolutionCallback::default();
+        multiplicity_matrix().solve(&mut expected);
+        assert_eq!(original.solutions, expected.solutions);
+        assert_eq!(cloned.solutions, expected.solutions);
+    }
+
+    #[test]
+    fn matrix_reports_its_dimensions() {
+        let mut mat = readme_matrix();
+        assert_eq!((mat.col_count(), mat.row_count()), (3, 6));
+        assert_eq!((1..=3).map(|c| mat.column_size(c)).collect::<Vec<_>>(), vec![3, 4, 3]);
+
+        mat.disable_row(1);
+        assert_eq!(mat.row_count(), 6);
+        assert_eq!((1..=3).map(|c| mat.column_size(c)).collect::<Vec<_>>(), vec![2, 3, 2]);
+    }
+
+    #[test]
+    fn callback_can_inspect_partial_solution() {
+        #[derive(Default)]
+        struct DepthCallback { max_len: usize, max_depth: usize }
+
+        impl Callback<Matrix> for DepthCallback {
+            fn on_iteration(&mut self, mat: &mut Matrix) {
+                assert!(mat.partial_solution().len() <= mat.current_depth());
+                self.max_len = self.max_len.max(mat.partial_solution().len());
+                self.max_depth = self.max_depth.max(mat.current_depth());
+            }
+        }
+
+        let mut mat = readme_matrix();
+        let mut callback = DepthCallback::default();
+        mat.solve(&mut callback);
+        assert_eq!(callback.max_len, 3); // B, C, D
+        assert_eq!(callback.max_depth, mat.stats().max_depth);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn shuffled_rows_keep_solutions() {
+        use rand::SeedableRng;
+
+        let sorted = |mut solutions: Vec<Vec<usize>>| {
+            for sol in &mut solutions { sol.sort_unstable(); }
+            solutions.sort();
+            solutions
+        };
+
+        let mut expected = SolutionCallback::default();
+        multiplicity_matrix().solve(&mut expected);
+
+        for seed in 0..8 {
+            let mut mat = multiplicity_matrix();
+            mat.disable_row(2);
+            mat.shuffle_rows(&mut rand::rngs::StdRng::seed_from_u64(seed));
+            mat.enable_row(2);
+
+            let mut callback = SolutionCallback::default();
+            mat.solve(&mut callback);
+            assert_eq!(sorted(callback.solutions), sorted(expected.solutions.clone()));
+        }
+    }
+
+    #[test]
+    fn forced_rows_are_kept_in_solutions() {
+        let mut mat = multiplicity_matrix();
+        mat.force_row(3).unwrap();
+        assert_eq!(mat.force_row(1), Err(MatrixError::ConflictingRow { row: 1 }));
+        assert_eq!(mat.partial_solution(), &[3]);
+
+        let mut callback = SolutionCallback::default();
+        mat.solve(&mut callback);
+        assert_eq!(callback.solutions, vec![vec![3, 4], vec![3, 2], vec![3]]);
+        assert_eq!(mat.partial_solution(), &[3]);
+    }
+
+    #[test]
+    fn search_statistics_are_counted() {
+        let mut mat = readme_matrix();
+        let mut callback = SolutionCallback::default();
+        mat.solve(&mut callback);
+
+        let stats = mat.stats();
+        assert_eq!(stats.solutions, callback.solutions.len());
+        assert!(stats.iterations > stats.solutions);
+        assert!(stats.backtracks > 0);
+        assert!(stats.max_depth > 0 && stats.max_depth <= 3);
+    }
+
+    #[test]
+    fn matrix_can_be_generated_from_problem() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let (mut mat, names) = Matrix::from_problem(&prob).unwrap();
+        assert_eq!(mat, readme_matrix());
+
+        let mut callback = SolutionCallback::default();
+        mat.solve(&mut callback);
+        let solutions: Vec<Vec<_>> = callback.solutions.iter()
+            .map(|sol| sol.iter().map(|&row| names[row - 1]).collect())
+            .collect();
+        assert_eq!(solutions, vec![vec!["A"], vec!["B", "C", "D"], vec!["B", "F"], vec!["E", "D"]]);
+
+        prob.add_subset("G", vec![4]);
+        assert_eq!(Matrix::from_problem(&prob), Err(ProblemError::UnknownElement { subset: 6, index: 0 }));
+    }
+
+    #[test]
+    fn pool_can_be_preallocated() {
+        let rows: &[&[usize]] = &[&[1, 2, 3], &[1], &[2], &[3], &[1, 2], &[2, 3]];
+        let mut mat = Matrix::with_capacity(3, 10);
+        let capacity = mat.pool.capacity();
+        assert!(capacity >= 1 + 3 + 10);
+
+        for row in rows { mat.add_row(row).unwrap(); }
+        assert_eq!(mat.pool.capacity(), capacity);
+        assert_eq!(mat, readme_matrix());
+    }
+
+    #[test]
+    fn progress_advances_with_top_level_branches() {
+        let mut mat = readme_matrix();
+        assert_eq!(mat.progress(), 0.0);
+
+        let mut progress = vec![];
+        while mat.step().is_some() {
+            progress.push(mat.progress());
+        }
+        assert!(progress.windows(2).all(|w| w[0] <= w[1]));
+        // Column 1 is chosen first, which has 3 rows
+        assert!(progress.contains(&(1.0 / 3.0)) && progress.contains(&(2.0 / 3.0)));
+        assert_eq!(mat.progress(), 1.0);
+    }
+
+    #[test]
+    fn dead_ends_report_uncovered_columns() {
+        #[derive(Default)]
+        struct DeadEndCallback { dead_ends: Vec<(usize, Vec<usize>)> }
+
+        impl Callback<Matrix> for DeadEndCallback {
+            fn on_dead_end(&mut self, col: usize, mat: &mut Matrix) {
+                self.dead_ends.push((col, mat.uncovered_columns()));
+            }
+        }
+
+        // Choosing the only row of column 1 leaves nothing for column 3
+        let mut mat = Matrix::with_rows(3, &[&[1, 2], &[2, 3]]).unwrap();
+        assert_eq!(mat.uncovered_columns(), vec![1, 2, 3]);
+        let mut callback = DeadEndCallback::default();
+        mat.solve(&mut callback);
+        assert_eq!(callback.dead_ends, vec![(3, vec![3])]);
+        assert_eq!(mat.stats().solutions, 0);
+    }
+
+    #[test]
+    fn weighted_progress_is_monotonic() {
+        use crate::problems::polyomino::PolyominoPacking;
+
+        // Pentominoes in a 3 x 20 rectangle
+        let mut packing = PolyominoPacking::rectangle(20, 3);
+        for (name, piece) in PolyominoPacking::standard_pentominoes() {
+            packing.add_piece(name, piece);
+        }
+        let (mut mat, _) = Matrix::from_problem(&packing.generate_problem()).unwrap();
+
+        let mut progress = vec![mat.progress()];
+        mat.step();
+        let (_, top_level) = mat.branch_stack[0];
+        while mat.step().is_some() {
+            progress.push(mat.progress());
+        }
+        assert!(progress.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(progress.first(), Some(&0.0));
+        assert_eq!(progress.last(), Some(&1.0));
+
+        // It advances inside the top-level branches as well
+        progress.dedup();
+        assert!(progress.len() > top_level + 1);
+    }
+
+    #[test]
+    fn untweak_rows_restores_column() {
+        let mut mat = multiplicity_matrix();
+        let (pool, col_size) = (mat.pool.clone(), mat.col_size.clone());
+
+        // Tweak every row of column 2 as the search does, then untweak them at once.
+        let first = mat.pool[2].down;
+        let mut r = first;
+        while r != 2 {
+            mat.tweak_row(r);
+            r = mat.pool[2].down;
+        }
+        assert_eq!(mat.pool[2].up, 2); // The column is empty now
+        mat.untweak_rows(first);
+
+        assert_eq!(mat.pool, pool);
+        assert_eq!(mat.col_size, col_size);
+    }
+
+    #[test]
+    fn iterative_solve_matches_recursive_solve() {
+        for mut mat in [readme_matrix(), multiplicity_matrix()] {
+            let mut recursive = SolutionCallback::default();
+            mat._recursive_solve(&mut recursive);
+
+            let mut iterative = SolutionCallback::default();
+            mat.solve(&mut iterative);
+
+            assert!(!iterative.solutions.is_empty());
+            assert_eq!(iterative.solutions, recursive.solutions);
+        }
+    }
+
+    #[test]
+    fn multiplicity_solutions_are_counted_correctly() {
+        // Column 1 takes exactly one of A and C, column 2 takes one or two rows,
+        // and column 3 takes at most one row: {A}, {A, B}, {C}, {C, B}, {C, D}.
+        let mut expected = vec![vec![1], vec![1, 2], vec![3], vec![2, 3], vec![3, 4]];
+        expected.sort();
+
+        let mut mat = multiplicity_matrix();
+        let mut recursive = SolutionCallback::default();
+        mat._recursive_solve(&mut recursive);
+        let mut iterative = SolutionCallback::default();
+        mat.solve(&mut iterative);
+
+        for mut solutions in [recursive.solutions, iterative.solutions] {
+            for sol in &mut solutions { sol.sort_unstable(); }
+            solutions.sort();
+            assert_eq!(solutions, expected);
+        }
+    }
+
+    #[test]
+    fn out_of_range_column_is_rejected() {
+        assert_eq!(
+            Matrix::with_rows(3, &[&[1, 2], &[3, 4]]).err(),
+            Some(MatrixError::ColumnOutOfRange { col: 4, col_cnt: 3 }),
+        );
+
+        let mut mat = readme_matrix();
+        assert!(mat.add_row(&[0, 1]).is_err());
+        assert_eq!(mat.row_cnt, 6);
+
+        let mut callback = SolutionCallback::default();
+        mat.solve(&mut callback);
+        assert_eq!(callback.solutions.len(), 4);
+    }
+
+    #[test]
+    fn cost_bound_prunes_expensive_solutions() {
+        let mut mat = readme_matrix();
+        mat.set_row_cost(1, 3); // A
+        mat.set_cost_bound(Some(3));
+
+        let mut callback = SolutionCallback::default();
+        mat.solve(&mut callback);
+        assert_eq!(callback.solutions, vec![vec![2, 6], vec![5, 4]]);
+        assert_eq!(mat.cost(), 0);
+    }
+
+    #[test]
+    fn partitions_find_each_solution_once() {
+        let mat = readme_matrix();
+        let mut solutions = vec![];
+
+        for index in 0..3 {
+            let mut mat = mat.clone();
+            mat.set_partition(index, 3);
+            let mut callback = SolutionCallback::default();
+            mat.solve(&mut callback);
+            solutions.extend(callback.solutions);
+        }
+
+        // Each top-level branch (row A, B, E in column 1) goes to a different partition
+        assert_eq!(solutions, vec![vec![1], vec![2, 3, 4], vec![2, 6], vec![5, 4]]);
+    }
+
+    #[test]
+    fn disabled_row_is_not_selected() {
+        let mut mat = readme_matrix();
+        mat.disable_row(1);
+
+        let mut callback = SolutionCallback::default();
+        mat.solve(&mut callback);
+        assert_eq!(callback.solutions, vec![vec![2, 3, 4], vec![2, 6], vec![5, 4]]);
+
+        mat.enable_row(1);
+
+        let mut callback = SolutionCallback::default();
+        mat.solve(&mut callback);
+        assert_eq!(callback.solutions, vec![vec![1], vec![2, 3, 4], vec![2, 6], vec![5, 4]]);
+    }
+
+    #[test]
+    fn matrix_can_be_exported() {
+        let mat = multiplicity_matrix();
+        assert_eq!(mat.to_dense_string(), "111\n010\n110\n011\n");
+
+        let sparse = mat.to_sparse_str();
+        assert_eq!(sparse, "p xcm 3 4\nm 1 1 1\nm 2 1 2\nm 3 0 1\n1 2 3 0\n2 0\n1 2 0\n2 3 0\n");
+        assert_eq!(Matrix::from_sparse_str(&sparse), Ok(mat));
+        assert_eq!(Matrix::from_sparse_str(&readme_matrix().to_sparse_str()), Ok(readme_matrix()));
+
+        assert!(Matrix::from_sparse_str("p xcm 1 0\nm 1 2 1\n").is_err());
+
+        // Multiplicities must precede the rows
+        let err = Matrix::from_sparse_str("p xcm 2 1\n1 0\nm 1 0 1\n");
+        assert_eq!(err, Err(ParseMatrixError { line: 3 }));
+    }
+
+    #[test]
+    fn named_matrix_can_be_exported() {
+        let mut mat = multiplicity_matrix();
+        mat.set_col_name(1, "x");
+        mat.set_col_name(3, "cell(0,1)");
+        assert_eq!(mat.column_name(1), Some("x"));
+        assert_eq!(mat.column_name(2), None);
+
+        assert_eq!(mat.to_dense_string(), "x 2 cell(0,1)\n111\n010\n110\n011\n");
+        let sparse = mat.to_sparse_str();
+        assert_eq!(sparse, "p xcm 3 4\nm 1 1 1\nm 2 1 2\nm 3 0 1\nn 1 x\nn 3 cell(0,1)\n1 2 3 0\n2 0\n1 2 0\n2 3 0\n");
+        assert_eq!(Matrix::from_sparse_str(&sparse), Ok(mat));
+
+        // Names do not affect the search
+        let mut named = readme_matrix();
+        for c in 1..=3 { named.set_col_name(c, c.to_string()); }
+        let mut callback = SolutionCallback::default();
+        named.solve(&mut callback);
+        assert_eq!(callback.solutions.len(), 4);
+
+        assert!(Matrix::from_sparse_str("p xcm 1 0\nn 1\n").is_err());
+        assert!(Matrix::from_sparse_str("p xcm 1 0\nn 2 x\n").is_err());
+        assert!(Matrix::from_sparse_str("p xcm 1 0\nn 1 x y\n").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid column name \"cell (0, 1)\"")]
+    fn column_name_with_spaces_is_rejected() {
+        readme_matrix().set_col_name(1, "cell (0, 1)");
+    }
+
+    #[test]
+    #[should_panic(expected = "Column 2 has a minimum multiplicity 3 greater than its maximum 1")]
+    fn invalid_multiplicity_is_rejected() {
+        let mut mat = readme_matrix();
+        mat.set_multiplicity(2, 3, 1);
+    }
+
+    #[test]
+    fn colored_rows_must_agree_on_colors() {
+        let colored_matrix = || {
+            let mut mat = Matrix::new(3);
+            mat.set_secondary(3);
+            for row in [
+                &[(1, 0), (3, 1)][..],
+                &[(2, 0), (3, 1)],
+                &[(2, 0), (3, 2)],
+                &[(1, 0)],
+                &[(2, 0), (3, 0)],
+            ] {
+                mat.add_colored_row(row).unwrap();
+            }
+            mat
+        };
+
+        let mut mat = colored_matrix();
+        assert_eq!(mat.add_colored_row(&[(1, 1)]), Err(MatrixError::ColoredPrimaryColumn { col: 1 }));
+        assert_eq!(mat.columns().collect::<Vec<_>>(), vec![1, 2]);
+
+        let mut callback = SolutionCallback::default();
+        mat.solve(&mut callback);
+        let mut solutions = callback.solutions;
+        for sol in &mut solutions { sol.sort_unstable(); }
+        solutions.sort();
+        assert_eq!(solutions, vec![vec![1, 2], vec![2, 4], vec![3, 4], vec![4, 5]]);
+
+        let mut forced = colored_matrix();
+        forced.force_row(1).unwrap();
+        assert_eq!(forced.force_row(3), Err(MatrixError::ConflictingRow { row: 3 }));
+        assert_eq!(forced.force_row(5), Err(MatrixError::ConflictingRow { row: 5 }));
+        let mut callback = SolutionCallback::default();
+        forced.solve(&mut callback);
+        assert_eq!(callback.solutions, vec![vec![1, 2]]);
+
+        let sparse = colored_matrix().to_sparse_str();
+        assert!(sparse.contains("\ns 3\n1 3:1 0\n"));
+        assert_eq!(Matrix::from_sparse_str(&sparse), Ok(colored_matrix()));
+        assert!(Matrix::from_sparse_str("p xcm 2 1\n1 2:1 0\n").is_err());
+    }
 }
@@ -3,6 +3,151 @@
 //! If you are looking for a [`Problem`](crate::problem::Problem) solver API,
 //! see the [`solver`](crate::solver) module.
 
+use std::error::Error;
+use std::fmt::Display;
+
+#[allow(clippy::module_inception)]
 pub mod dlx;
 pub mod dlx_m;
 pub mod callback;
+pub mod chooser;
+
+/// An error returned when a matrix cannot be modified as requested.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MatrixError {
+    /// The row contains a column `col` out of the range `1..=col_cnt`.
+    ColumnOutOfRange { col: usize, col_cnt: usize },
+    /// The row `row` cannot be forced, because it is disabled
+    /// or exceeds the maximum multiplicity of a column together with the other forced rows.
+    ConflictingRow { row: usize },
+    /// The row gives a color to column `col`, which is not a secondary column.
+    ColoredPrimaryColumn { col: usize },
+}
+
+impl Display for MatrixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatrixError::ColumnOutOfRange { col, col_cnt } =>
+                write!(f, "Column {} is out of range 1..={}.", col, col_cnt),
+            MatrixError::ConflictingRow { row } =>
+                write!(f, "Row {} conflicts with the forced rows.", row),
+            MatrixError::ColoredPrimaryColumn { col } =>
+                write!(f, "Column {} is not secondary, so it cannot have a color.", col),
+        }
+    }
+}
+
+impl Error for MatrixError {}
+
+/// An error returned when a matrix cannot be parsed from the sparse text format.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ParseMatrixError {
+    /// The line (1-based) which cannot be parsed.
+    /// It is one past the last line if the input ends before all rows are given.
+    pub line: usize,
+}
+
+impl Display for ParseMatrixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid matrix format at line {}.", self.line)
+    }
+}
+
+impl Error for ParseMatrixError {}
+
+/// The contents of a matrix in the sparse text format.
+/// 
+/// The format is similar to DIMACS CNF format.
+/// The problem line `p <kind> <col_cnt> <row_cnt>` is followed by the lines
+/// `m <col> <min> <max>` for column multiplicities, `s <col>` for secondary columns,
+/// and `n <col> <name>` for column names (only for `xcm` kind), and then by a line for each row,
+/// listing its columns (1-based) terminated by `0`.
+/// A colored node is written as `<col>:<color>` (only for `xcm` kind).
+/// Empty lines and lines starting with `c` are ignored.
+pub(crate) struct SparseMatrix {
+    pub col_cnt: usize,
+    pub multiplicities: Vec<(usize, usize, usize)>,
+    pub secondary: Vec<usize>,
+    pub names: Vec<(usize, String)>,
+    pub rows: Vec<Vec<(usize, usize)>>, // (column, color)
+}
+
+impl SparseMatrix {
+    pub fn parse(s: &str, kind: &str) -> Result<SparseMatrix, ParseMatrixError> {
+        let mut header = None;
+        let mut multiplicities = vec![];
+        let mut secondary = vec![];
+        let mut names = vec![];
+        let mut rows = vec![];
+        let mut line_cnt = 0;
+
+        for (i, line) in s.lines().enumerate() {
+            let err = ParseMatrixError { line: i + 1 };
+            let tokens: Vec<_> = line.split_whitespace().collect();
+            line_cnt = i + 1;
+
+            match (tokens.first().copied(), header) {
+                (None | Some("c"), _) => (),
+                (Some("p"), None) => {
+                    if tokens.get(1) != Some(&kind) { return Err(err) }
+                    let [col_cnt, row_cnt] = parse_numbers(&tokens[2..]).ok_or(err)?[..] else { return Err(err) };
+                    header = Some((col_cnt, row_cnt));
+                }
+                (Some("m"), Some((col_cnt, _))) if kind == "xcm" && rows.is_empty() => {
+                    let [col, min, max] = parse_numbers(&tokens[1..]).ok_or(err)?[..] else { return Err(err) };
+                    if col == 0 || col > col_cnt || min > max { return Err(err) }
+                    multiplicities.push((col, min, max));
+                }
+                (Some("s"), Some((col_cnt, _))) if kind == "xcm" && rows.is_empty() => {
+                    let [col] = parse_numbers(&tokens[1..]).ok_or(err)?[..] else { return Err(err) };
+                    if col == 0 || col > col_cnt { return Err(err) }
+                    secondary.push(col);
+                }
+                (Some("n"), Some((col_cnt, _))) if kind == "xcm" && rows.is_empty() => {
+                    let [col, name] = tokens[1..] else { return Err(err) };
+                    let col: usize = col.parse().map_err(|_| err)?;
+                    if col == 0 || col > col_cnt { return Err(err) }
+                    names.push((col, name.to_string()));
+                }
+                (Some(_), Some((col_cnt, row_cnt))) => {
+                    if rows.len() == row_cnt || tokens.last() != Some(&"0") { return Err(err) }
+                    let row = tokens[..tokens.len() - 1].iter()
+                        .map(|token| match token.split_once(':') {
+                            Some((col, color)) if kind == "xcm" => Some((col.parse().ok()?, color.parse().ok()?)),
+                            Some(_) => None,
+                            None => Some((token.parse().ok()?, 0)),
+                        })
+                        .collect::<Option<Vec<(usize, usize)>>>()
+                        .ok_or(err)?;
+                    if row.iter().any(|&(col, _)| col == 0 || col > col_cnt) { return Err(err) }
+                    if row.iter().any(|&(col, color)| color != 0 && (color == usize::MAX || !secondary.contains(&col))) {
+                        return Err(err)
+                    }
+                    rows.push(row);
+                }
+                _ => return Err(err),
+            }
+        }
+
+        match header {
+            Some((col_cnt, row_cnt)) if rows.len() == row_cnt => {
+                Ok(SparseMatrix { col_cnt, multiplicities, secondary, names, rows })
+            }
+            _ => Err(ParseMatrixError { line: line_cnt + 1 }),
+        }
+    }
+
+    pub fn row_line(row: &[(usize, usize)]) -> String {
+        row.iter().chain(&[(0, 0)])
+            .map(|&(col, color)| match color {
+                0 => col.to_string(),
+                _ => format!("{}:{}", col, color),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn parse_numbers(tokens: &[&str]) -> Option<Vec<usize>> {
+    tokens.iter().map(|t| t.parse().ok()).collect()
+}
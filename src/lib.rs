@@ -15,8 +15,9 @@
 //! # Basic example
 //! 
 //! ```
-//! use exact_cover::{Problem, Solver, SolverEvent};
+//! use exact_cover::{Problem, Solver};
 //! 
+//! # #[cfg(feature = "threads")]
 //! fn main() {
 //!     let mut prob = Problem::default();
 //!     prob.add_exact_constraints(1..=3);
@@ -27,23 +28,29 @@
 //!     prob.add_subset("E", vec![1, 2]);
 //!     prob.add_subset("F", vec![2, 3]);
 //! 
-//!     let mut solver = Solver::new(prob);
-//!     let mut solutions = vec![];
-//!     solver.run();
-//! 
-//!     for event in solver {
-//!         if let SolverEvent::SolutionFound(sol) = event {
-//!             solutions.push(sol);
-//!         }
-//!     }
+//!     let solutions = Solver::new(prob).solve_all().unwrap();
 //! 
 //!     println!("{:?}", solutions); // [["A"], ["B", "C", "D"], ["B", "F"], ["E", "D"]]
 //! }
+//! # #[cfg(not(feature = "threads"))]
+//! # fn main() {}
 //! ```
 //! 
-//! # Asynchronous API
+//! # Feature flags
 //! 
-//! ⚠️ The feature is not available yet.
+//! - `threads` (default): runs the solver in a background thread
+//!   (see [`Solver::run`]). Without it, only the blocking API such as
+//!   [`Solver::solve_blocking`] is available, e.g. for `wasm32-unknown-unknown`.
+//! - `async`: provides the solver events as a stream (see `Solver::into_stream`).
+//! - `serde`: implements `Serialize` and `Deserialize` for [`Problem`].
+//! - `rand`: shuffles the order of the subsets to try with a seed (see `Solver::with_seed`).
+//! - `ansi`: draws polyomino packing solutions in colors for terminals
+//!   (see `PolyominoPacking::render_solution_ansi`).
+//! - `smallvec`: stores small subsets inline without allocating (see [`problem::Subset`]).
+//! - `rayon`: generates the polyomino packing placements in parallel
+//!   (see `PolyominoPacking::par_generate_problem`).
+//! 
+//! # Asynchronous API
 //! 
 //! Solving a complex exact cover problem takes a long time.
 //! Users don't want to wait for the solving process to end without knowing
@@ -51,19 +58,22 @@
 //! This library provides an asynchronous API and various features to help with this issue.
 //! 
 //! - Thanks to the asynchronous API, your program doesn't have to wait for the solver
-//!   until it finds the next solution.
-//! - You can fetch the estimated progress of the solving process, anytime you want.
+//!   until it finds the next solution (see [`Solver::run`], or `Solver::into_stream` with the `async` feature).
+//! - You can fetch the estimated progress of the solving process, anytime you want
+//!   (see [`Solver::request_progress`]).
 //! - When the search space is too large and the solving process is not going to end in centuries,
-//!   you can abort the solver.
-//! - You can pause the solving process and save the solver state to resume later.
+//!   you can abort the solver (see [`Solver::abort`]).
+//! - You can pause the solving process (see [`Solver::pause`]),
+//!   and save the state of an aborted solver to resume later (see [`Solver::resume`]).
 
 pub mod vector;
 
 pub mod dlx;
 pub mod problem;
 pub mod solver;
+pub mod trie;
 
 pub mod problems;
 
-pub use problem::Problem;
-pub use solver::{Solver, SolverEvent};
+pub use problem::{Problem, ProblemBuilder};
+pub use solver::{Solver, SolverEvent, StepEvent};
@@ -4,13 +4,154 @@
 //! this basic [`Problem`] instance before handing it to a solver.
 //! To see examples of more complex problems, see [`problems`](crate::problems) module.
 
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{Debug, Display};
 use std::hash::Hash;
-use indexmap::{IndexMap};
+use std::ops::Deref;
+use indexmap::{IndexMap, IndexSet};
+use crate::dlx::callback::Callback;
+use crate::dlx::dlx_m::Matrix;
 
 /// Base trait for subset names and set elements.
 pub trait Value: Clone + Hash + Eq {}
 impl<T: Clone + Hash + Eq> Value for T {}
 
+/// The elements of a subset as stored in a [`Problem`].
+/// 
+/// It dereferences to a slice, and compares equal to a `Vec` of the same elements.
+/// With the `smallvec` feature enabled, small subsets are stored inline without allocating,
+/// but the type and its methods stay the same.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct Subset<E>(SubsetVec<E>);
+
+#[cfg(not(feature = "smallvec"))]
+type SubsetVec<E> = Vec<E>;
+// It fits a pentomino piece together with its piece constraint.
+#[cfg(feature = "smallvec")]
+type SubsetVec<E> = smallvec::SmallVec<[E; 6]>;
+
+impl<E> Subset<E> {
+    pub(crate) fn new() -> Subset<E> { Subset(SubsetVec::new()) }
+    pub(crate) fn push(&mut self, elem: E) { self.0.push(elem); }
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&E) -> bool) { self.0.retain(|e| f(e)); }
+}
+
+impl<E> Deref for Subset<E> {
+    type Target = [E];
+    fn deref(&self) -> &[E] { &self.0 }
+}
+
+impl<E: Debug> Debug for Subset<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { self.0.fmt(f) }
+}
+
+impl<E: PartialEq> PartialEq<Vec<E>> for Subset<E> {
+    fn eq(&self, other: &Vec<E>) -> bool { self[..] == other[..] }
+}
+
+impl<E> From<Vec<E>> for Subset<E> {
+    fn from(subset: Vec<E>) -> Subset<E> { Subset(SubsetVec::from(subset)) }
+}
+
+impl<E> From<Subset<E>> for Vec<E> {
+    #[cfg(not(feature = "smallvec"))]
+    fn from(subset: Subset<E>) -> Vec<E> { subset.0 }
+    #[cfg(feature = "smallvec")]
+    fn from(subset: Subset<E>) -> Vec<E> { subset.0.into_vec() }
+}
+
+impl<E> FromIterator<E> for Subset<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Subset<E> { Subset(iter.into_iter().collect()) }
+}
+
+impl<E> Extend<E> for Subset<E> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) { self.0.extend(iter); }
+}
+
+impl<'a, E> IntoIterator for &'a Subset<E> {
+    type Item = &'a E;
+    type IntoIter = std::slice::Iter<'a, E>;
+    fn into_iter(self) -> std::slice::Iter<'a, E> { self.iter() }
+}
+
+/// An error returned when a problem is malformed.
+/// 
+/// Subsets and their elements are referred to by their indices in the insertion order.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ProblemError {
+    /// The `index`-th element of the `subset`-th subset is not a constraint of the problem.
+    UnknownElement { subset: usize, index: usize },
+    /// The `subset`-th subset has no elements.
+    EmptySubset { subset: usize },
+    /// The `subset`-th subset is forced, but it exceeds the maximum multiplicity
+    /// of a constraint together with the previously forced subsets.
+    ConflictingSubset { subset: usize },
+    /// The `index`-th element of the `subset`-th subset has a color,
+    /// but it is not a secondary constraint.
+    ColoredPrimaryConstraint { subset: usize, index: usize },
+    /// The minimum multiplicity of the `constraint`-th constraint is greater than its maximum.
+    InvalidMultiplicity { constraint: usize },
+    /// The `forced`-th forced subset is not a subset of the problem,
+    /// e.g. in a deserialized problem.
+    UnknownForcedSubset { forced: usize },
+}
+
+impl Display for ProblemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProblemError::UnknownElement { subset, index } =>
+                write!(f, "Element {} of subset {} is not a constraint.", index, subset),
+            ProblemError::EmptySubset { subset } =>
+                write!(f, "Subset {} is empty.", subset),
+            ProblemError::ConflictingSubset { subset } =>
+                write!(f, "Forced subset {} conflicts with the other forced subsets.", subset),
+            ProblemError::ColoredPrimaryConstraint { subset, index } =>
+                write!(f, "Element {} of subset {} has a color, but it is not a secondary constraint.", index, subset),
+            ProblemError::InvalidMultiplicity { constraint } =>
+                write!(f, "Constraint {} has a minimum multiplicity greater than its maximum.", constraint),
+            ProblemError::UnknownForcedSubset { forced } =>
+                write!(f, "Forced subset {} is not a subset of the problem.", forced),
+        }
+    }
+}
+
+impl Error for ProblemError {}
+
+/// An error returned when two problems cannot be merged by [`Problem::union`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum UnionError<N, E> {
+    /// Both problems have a subset with this name.
+    DuplicateSubset(N),
+    /// Both problems have this constraint, but their multiplicity ranges do not overlap,
+    /// or it is secondary in only one of them.
+    ConflictingConstraint(E),
+}
+
+impl<N: Debug, E: Debug> Display for UnionError<N, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnionError::DuplicateSubset(name) => write!(f, "Duplicate subset {:?}.", name),
+            UnionError::ConflictingConstraint(elem) => write!(f, "Conflicting constraint {:?}.", elem),
+        }
+    }
+}
+
+impl<N: Debug, E: Debug> Error for UnionError<N, E> {}
+
+/// An error returned by [`Problem::try_add_subset`] when the subset name already exists.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DuplicateName<N>(pub N);
+
+impl<N: Debug> Display for DuplicateName<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Duplicate subset name {:?}.", self.0)
+    }
+}
+
+impl<N: Debug> Error for DuplicateName<N> {}
+
 /// An exact cover problem instance.
 /// 
 /// The set elements are of type `E`.
@@ -21,52 +162,550 @@ impl<T: Clone + Hash + Eq> Value for T {}
 /// 
 /// The order of the subsets and the elements is determined by the insertion order.
 /// It uses [`IndexMap`] internally to keep track of the order.
-/// The subset order may affect the order of the solutions.
-#[derive(Clone)]
+/// 
+/// The solutions are emitted in the order the search finds them, which is determined by these orders:
+/// - At each step, the search branches on the constraint with the fewest subsets left.
+///   Among such constraints, the one inserted first is chosen
+///   (see [`Mrv`](crate::dlx::chooser::Mrv)).
+/// - The subsets covering the chosen constraint are tried in the insertion order.
+/// - Each solution lists its subsets in the order they were chosen, after the forced subsets.
+/// 
+/// So the same problem always yields the same solutions in the same order,
+/// e.g. `[["A"], ["B", "C", "D"], ["B", "F"], ["E", "D"]]` for the example in the crate documentation.
+/// The subsets covering a constraint are tried in a shuffled order instead
+/// if the solver is given a seed by `Solver::with_seed`.
+/// The constraints can be reordered by [`sort_constraints_by`](Problem::sort_constraints_by)
+/// to change the search order without changing the solutions.
+/// 
+/// # Costs
+/// 
+/// Each subset costs 1 unless added by [`add_weighted_subset`](Problem::add_weighted_subset).
+/// The costs are only used to find a minimum-cost solution
+/// (see [`Solver::solve_minimum`](crate::Solver::solve_minimum)).
+/// 
+/// # Forced subsets
+/// 
+/// Subsets can be forced by [`force_subset`](Problem::force_subset),
+/// e.g. the givens of a Sudoku puzzle or a pre-placed piece.
+/// Every solution contains the forced subsets, in front of the other subsets.
+/// 
+/// # Colors
+/// 
+/// A secondary constraint (see [`add_secondary_constraint`](Problem::add_secondary_constraint))
+/// is covered at most once, unless every subset covering it gives it the same color
+/// (see [`add_colored_subset`](Problem::add_colored_subset)).
+/// It is the exact cover with colors (XCC) problem,
+/// e.g. the edges of adjacent tiles have to match each other.
+/// 
+/// # Serialization
+/// 
+/// With the `serde` feature enabled, the problem implements `Serialize` and `Deserialize`.
+/// The insertion order of the subsets and the elements is preserved.
+/// 
+/// # Builder
+/// 
+/// [`ProblemBuilder`] builds a validated problem by chaining method calls.
+#[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Problem<N: Value, E: Value> {
     constraints: IndexMap<E, (usize, usize)>,
-    subsets: IndexMap<N, Vec<E>>,
+    subsets: IndexMap<N, Subset<E>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    costs: IndexMap<N, usize>, // only for the subsets not costing 1
+    #[cfg_attr(feature = "serde", serde(default))]
+    forced: IndexSet<N>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    secondary: IndexSet<E>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    colors: IndexMap<N, IndexMap<E, usize>>, // only for the colored subsets
 }
 
 impl<N: Value, E: Value> Default for Problem<N, E> {
     fn default() -> Problem<N, E> {
-        Problem { constraints: Default::default(), subsets: Default::default() }
+        Problem {
+            constraints: Default::default(),
+            subsets: Default::default(),
+            costs: Default::default(),
+            forced: Default::default(),
+            secondary: Default::default(),
+            colors: Default::default(),
+        }
+    }
+}
+
+/// Collects `(name, subset)` pairs into a problem without constraints,
+/// as if they were added by [`add_subset`](Problem::add_subset) in order.
+/// 
+/// The subsets are either `Vec`s or [`Subset`]s, e.g. cloned from another problem.
+/// 
+/// See [`from_subsets`](Problem::from_subsets) to add the exact constraints as well.
+impl<N: Value, E: Value, S: Into<Subset<E>>> FromIterator<(N, S)> for Problem<N, E> {
+    fn from_iter<I: IntoIterator<Item = (N, S)>>(iter: I) -> Problem<N, E> {
+        let mut prob = Problem::default();
+        for (name, subset) in iter {
+            prob.insert_subset(name, subset.into());
+        }
+        prob
     }
 }
 
 impl<N: Value, E: Value> Problem<N, E> {
-    // TODO: hide IndexMap/IndexSet from API
+    /// Creates a problem from `(name, subset)` pairs, where every element of the subsets
+    /// is an exact constraint (see [`infer_exact_constraints`](Problem::infer_exact_constraints)).
+    pub fn from_subsets<S: Into<Subset<E>>, I: IntoIterator<Item = (N, S)>>(subsets: I) -> Problem<N, E> {
+        let mut prob: Problem<N, E> = subsets.into_iter().collect();
+        prob.infer_exact_constraints();
+        prob
+    }
+
     /// Returns a reference to the constraints of the problem.
+    /// 
+    /// Prefer [`constraint_ranges`](Problem::constraint_ranges),
+    /// which does not depend on the version of `indexmap`.
     pub fn constraints(&self) -> &IndexMap<E, (usize, usize)> { &self.constraints }
     /// Returns a reference to the subsets of the problem.
-    pub fn subsets(&self) -> &IndexMap<N, Vec<E>> { &self.subsets }
+    /// 
+    /// The subsets are stored as [`Subset`] rather than `Vec`, even without the `smallvec` feature.
+    /// They dereference to slices and compare equal to `Vec`s,
+    /// and `Vec::from` converts them back.
+    /// 
+    /// Prefer [`subset_names`](Problem::subset_names) and [`subset`](Problem::subset),
+    /// which return plain slices and do not depend on the version of `indexmap`.
+    pub fn subsets(&self) -> &IndexMap<N, Subset<E>> { &self.subsets }
+
+    /// Returns the names of the subsets in the insertion order.
+    pub fn subset_names(&self) -> impl Iterator<Item = &N> + '_ { self.subsets.keys() }
+    /// Returns the elements of a subset, if it exists.
+    pub fn subset(&self, name: &N) -> Option<&[E]> { self.subsets.get(name).map(|subset| &subset[..]) }
+    /// Returns the constraints with their multiplicity ranges in the insertion order.
+    pub fn constraint_ranges(&self) -> impl Iterator<Item = (&E, (usize, usize))> + '_ {
+        self.constraints.iter().map(|(e, &range)| (e, range))
+    }
 
     /// Adds a subset to the problem.
     /// 
-    /// If the subset name already exists, it replaces the corresponding subset.
+    /// If the subset name already exists, it replaces the corresponding subset,
+    /// dropping its cost and colors as well.
+    /// Use [`try_add_subset`](Problem::try_add_subset) to keep the existing subset instead.
     pub fn add_subset(&mut self, name: N, subset: Vec<E>) {
+        self.insert_subset(name, Subset::from(subset));
+    }
+
+    /// Adds a subset to the problem, unless the subset name already exists.
+    /// 
+    /// It returns the name back as an error if it already exists,
+    /// leaving the existing subset untouched.
+    pub fn try_add_subset(&mut self, name: N, subset: Vec<E>) -> Result<(), DuplicateName<N>> {
+        if self.subsets.contains_key(&name) { return Err(DuplicateName(name)); }
+        self.add_subset(name, subset);
+        Ok(())
+    }
+
+    /// Adds a subset that gives colors to some secondary constraints.
+    /// 
+    /// The colored elements follow the uncolored elements `subset` in the subset.
+    /// If the subset name already exists, it replaces the corresponding subset.
+    pub fn add_colored_subset(&mut self, name: N, subset: Vec<E>, colors: Vec<(E, usize)>) {
+        self.insert_colored_subset(name, Subset::from(subset), colors);
+    }
+
+    // The problem generators build the subsets in the storage type directly,
+    // so that the small subsets are not allocated with the `smallvec` feature.
+
+    pub(crate) fn insert_subset(&mut self, name: N, subset: Subset<E>) {
+        self.costs.shift_remove(&name);
+        self.colors.shift_remove(&name);
         self.subsets.insert(name, subset);
     }
 
+    pub(crate) fn insert_colored_subset(&mut self, name: N, mut subset: Subset<E>, colors: Vec<(E, usize)>) {
+        subset.extend(colors.iter().map(|(e, _)| e.clone()));
+        self.insert_subset(name.clone(), subset);
+        if !colors.is_empty() { self.colors.insert(name, colors.into_iter().collect()); }
+    }
+
+    /// Returns the color that a subset gives to an element, if any.
+    pub fn color(&self, name: &N, elem: &E) -> Option<usize> {
+        self.colors.get(name)?.get(elem).copied()
+    }
+
+    /// Adds a subset with a cost.
+    /// 
+    /// If the subset name already exists, it replaces the corresponding subset.
+    pub fn add_weighted_subset(&mut self, name: N, subset: Vec<E>, cost: usize) {
+        self.add_subset(name.clone(), subset);
+        if cost != 1 { self.costs.insert(name, cost); }
+    }
+
+    /// Returns the cost of a subset, which is 1 by default.
+    pub fn cost(&self, name: &N) -> usize {
+        self.costs.get(name).copied().unwrap_or(1)
+    }
+
+    /// Forces a subset to be contained in every solution.
+    /// 
+    /// It returns `false` if there is no such subset.
+    /// Whether the forced subsets conflict with each other is checked by [`validate`](Problem::validate).
+    pub fn force_subset(&mut self, name: N) -> bool {
+        if !self.subsets.contains_key(&name) { return false; }
+        self.forced.insert(name);
+        true
+    }
+
+    /// Returns the forced subsets in the order they were forced.
+    pub fn forced_subsets(&self) -> &IndexSet<N> { &self.forced }
+
     /// Adds a constraint with a multiplicity range.
+    /// 
+    /// A range whose `min` is greater than `max` is reported by [`validate`](Problem::validate).
     pub fn add_constraint(&mut self, elem: E, min: usize, max: usize) {
+        self.secondary.shift_remove(&elem);
         self.constraints.insert(elem, (min, max));
     }
 
+    /// Adds a secondary constraint, which can be colored (see [Colors](Problem#colors)).
+    /// 
+    /// It does not have to be covered, and it can be covered at most once without colors.
+    pub fn add_secondary_constraint(&mut self, elem: E) {
+        self.add_constraint(elem.clone(), 0, 1);
+        self.secondary.insert(elem);
+    }
+
+    /// Returns the secondary constraints in the order they were added.
+    pub fn secondary_constraints(&self) -> &IndexSet<E> { &self.secondary }
+
     /// Adds a constraint that has to be covered exactly once.
     pub fn add_exact_constraint(&mut self, elem: E) {
         self.add_constraint(elem, 1, 1);
     }
     
+    /// Adds an optional constraint, which can be covered at most once but does not have to be.
+    /// 
+    /// It is the same as `add_constraint(elem, 0, 1)`.
+    /// Unlike a secondary constraint, it is still a primary column of the matrix,
+    /// so the search may branch on it (see [`Mrv`](crate::dlx::chooser::Mrv)),
+    /// trying each subset covering it and then leaving it uncovered. It cannot be colored.
+    pub fn add_optional_constraint(&mut self, elem: E) {
+        self.add_constraint(elem, 0, 1);
+    }
+
+    /// Adds a constraint that has to be covered at least `min` times, without a maximum.
+    /// 
+    /// With such constraints, a minimum-cost solution
+    /// (see [`Solver::solve_minimum`](crate::Solver::solve_minimum)) is a minimum set cover,
+    /// i.e. the fewest subsets covering every element if all subsets cost 1.
+    pub fn add_cover_constraint(&mut self, elem: E, min: usize) {
+        self.add_constraint(elem, min, usize::MAX);
+    }
+
     /// Adds several exact constraints.
     pub fn add_exact_constraints<I: IntoIterator<Item = E>>(&mut self, constraints: I) {
         for constraint in constraints {
             self.add_exact_constraint(constraint);
         }
     }
+
+    /// Adds every element of the subsets that is not a constraint yet as an exact constraint,
+    /// in the order they appear in the subsets.
+    /// 
+    /// The constraints already added keep their multiplicity ranges.
+    pub fn infer_exact_constraints(&mut self) {
+        let undeclared: IndexSet<E> = self.subsets.values().flatten()
+            .filter(|&e| !self.constraints.contains_key(e))
+            .cloned()
+            .collect();
+        self.add_exact_constraints(undeclared);
+    }
+
+    /// Removes a subset from the problem, and returns it if it existed.
+    /// 
+    /// The order of the remaining subsets is preserved.
+    pub fn remove_subset(&mut self, name: &N) -> Option<Vec<E>> {
+        self.costs.shift_remove(name);
+        self.forced.shift_remove(name);
+        self.colors.shift_remove(name);
+        self.subsets.shift_remove(name).map(Vec::from)
+    }
+
+    /// Removes a constraint from the problem, and returns its multiplicity range if it existed.
+    /// 
+    /// The element is also dropped from every subset that contains it.
+    /// The order of the remaining constraints and subset elements is preserved.
+    pub fn remove_constraint(&mut self, elem: &E) -> Option<(usize, usize)> {
+        let range = self.constraints.shift_remove(elem)?;
+        for subset in self.subsets.values_mut() {
+            subset.retain(|e| e != elem);
+        }
+        self.secondary.shift_remove(elem);
+        for colors in self.colors.values_mut() {
+            colors.shift_remove(elem);
+        }
+        self.colors.retain(|_, colors| !colors.is_empty());
+        Some(range)
+    }
+
+    /// Sorts the constraints with a comparison function,
+    /// given the elements with their multiplicity ranges, e.g. to put hard constraints first.
+    /// 
+    /// Among the constraints with the fewest subsets left, the search branches on the first one in this order
+    /// (see [Ordering](Problem#ordering)), so it changes the order of the solutions, but not the solutions themselves.
+    /// The sort is stable, and the order of the elements within each subset is left as is.
+    pub fn sort_constraints_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&E, (usize, usize), &E, (usize, usize)) -> Ordering,
+    {
+        self.constraints.sort_by(|e1, &r1, e2, &r2| compare(e1, r1, e2, r2));
+    }
+
+    /// Merges the constraints and the subsets of two problems into one,
+    /// e.g. to add extra constraints to a base problem built separately.
+    /// 
+    /// A constraint in both problems is restricted to the intersection of the two multiplicity ranges.
+    /// It returns an error if the ranges do not overlap, if the constraint is secondary in only one of them,
+    /// or if both problems have a subset with the same name.
+    /// The subsets of `other` keep their costs, colors and whether they are forced,
+    /// and follow the ones of `self` in the insertion order.
+    pub fn union(mut self, other: Problem<N, E>) -> Result<Problem<N, E>, UnionError<N, E>> {
+        for (elem, (min, max)) in other.constraints {
+            let secondary = other.secondary.contains(&elem);
+            match self.constraints.get_mut(&elem) {
+                Some(range) => {
+                    let merged = (range.0.max(min), range.1.min(max));
+                    if merged.0 > merged.1 || self.secondary.contains(&elem) != secondary {
+                        return Err(UnionError::ConflictingConstraint(elem));
+                    }
+                    *range = merged;
+                }
+                None if secondary => self.add_secondary_constraint(elem),
+                None => self.add_constraint(elem, min, max),
+            }
+        }
+
+        for (name, subset) in other.subsets {
+            if self.subsets.contains_key(&name) {
+                return Err(UnionError::DuplicateSubset(name));
+            }
+            self.subsets.insert(name, subset);
+        }
+        self.costs.extend(other.costs);
+        self.forced.extend(other.forced);
+        self.colors.extend(other.colors);
+        Ok(self)
+    }
+
+    /// Checks that every constraint has a valid multiplicity range,
+    /// that every subset is non-empty
+    /// and only consists of the constraints of the problem,
+    /// that only the secondary constraints are colored,
+    /// and that the forced subsets do not exceed the maximum multiplicities
+    /// or disagree on the colors.
+    /// 
+    /// It returns the first error found in the insertion order.
+    pub fn validate(&self) -> Result<(), ProblemError> {
+        if let Some(constraint) = self.constraints.values().position(|&(min, max)| min > max) {
+            return Err(ProblemError::InvalidMultiplicity { constraint });
+        }
+
+        for (subset, (name, elems)) in self.subsets.iter().enumerate() {
+            if elems.is_empty() {
+                return Err(ProblemError::EmptySubset { subset });
+            }
+            if let Some(index) = elems.iter().position(|e| !self.constraints.contains_key(e)) {
+                return Err(ProblemError::UnknownElement { subset, index });
+            }
+            if let Some(index) = elems.iter().position(|e| {
+                self.color(name, e).is_some() && !self.secondary.contains(e)
+            }) {
+                return Err(ProblemError::ColoredPrimaryConstraint { subset, index });
+            }
+        }
+
+        let mut count: IndexMap<&E, usize> = IndexMap::new();
+        let mut first_colors: IndexMap<&E, Option<usize>> = IndexMap::new();
+        for (forced, name) in self.forced.iter().enumerate() {
+            let (subset, _, elems) = self.subsets.get_full(name)
+                .ok_or(ProblemError::UnknownForcedSubset { forced })?;
+            for elem in elems {
+                let cnt = count.entry(elem).or_insert(0);
+                *cnt += 1;
+                let conflicts = if self.secondary.contains(elem) {
+                    let color = self.color(name, elem);
+                    let first = *first_colors.entry(elem).or_insert(color);
+                    *cnt > 1 && (color.is_none() || color != first)
+                } else {
+                    *cnt > self.constraints[elem].1
+                };
+                if conflicts {
+                    return Err(ProblemError::ConflictingSubset { subset });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the constraints contained in fewer subsets than their minimum multiplicity,
+    /// e.g. an exact constraint that no subset contains.
+    /// 
+    /// If any, the problem has no solution.
+    /// It is a quick check that does not run the search.
+    pub fn uncoverable_constraints(&self) -> Vec<E> {
+        let mut count: IndexMap<&E, usize> = self.constraints.keys().map(|e| (e, 0)).collect();
+        for elem in self.subsets.values().flatten() {
+            if let Some(cnt) = count.get_mut(elem) { *cnt += 1; }
+        }
+
+        count.into_iter()
+            .filter(|&(e, cnt)| cnt < self.constraints[e].0)
+            .map(|(e, _)| e.clone())
+            .collect()
+    }
+
+    /// Returns the constraints whose maximum multiplicity exceeds
+    /// the number of subsets containing them, e.g. a constraint that allows `0..=2`
+    /// but is contained in a single subset.
+    /// 
+    /// The maximum can never be reached, which is often a mistake in building the problem,
+    /// although the problem may still have solutions. Secondary constraints
+    /// and the constraints without a maximum (see [`add_cover_constraint`](Problem::add_cover_constraint))
+    /// are not reported.
+    pub fn unreachable_maximums(&self) -> Vec<E> {
+        let mut count: IndexMap<&E, usize> = self.constraints.keys().map(|e| (e, 0)).collect();
+        for elem in self.subsets.values().flatten() {
+            if let Some(cnt) = count.get_mut(elem) { *cnt += 1; }
+        }
+
+        count.into_iter()
+            .filter(|&(e, cnt)| {
+                let max = self.constraints[e].1;
+                !self.secondary.contains(e) && max != usize::MAX && cnt < max
+            })
+            .map(|(e, _)| e.clone())
+            .collect()
+    }
+
+    /// Returns the constraints that are not covered within their multiplicity ranges
+    /// by the subsets named `sol`, including the secondary constraints
+    /// covered more than once with different colors or without colors.
+    /// 
+    /// Names that are not in the problem are ignored.
+    pub fn violated_constraints(&self, sol: &[N]) -> Vec<E> {
+        let mut count: IndexMap<&E, usize> = self.constraints.keys().map(|e| (e, 0)).collect();
+        let mut colors: IndexMap<&E, IndexSet<Option<usize>>> = IndexMap::new();
+        for name in sol.iter().filter(|&name| self.subsets.contains_key(name)) {
+            for elem in &self.subsets[name] {
+                if let Some(cnt) = count.get_mut(elem) { *cnt += 1; }
+                if self.secondary.contains(elem) {
+                    colors.entry(elem).or_default().insert(self.color(name, elem));
+                }
+            }
+        }
+
+        count.into_iter()
+            .filter(|&(e, cnt)| {
+                if self.secondary.contains(e) {
+                    return cnt > 1 && (colors[e].len() > 1 || colors[e].contains(&None));
+                }
+                let (min, max) = self.constraints[e];
+                cnt < min || cnt > max
+            })
+            .map(|(e, _)| e.clone())
+            .collect()
+    }
+
+    /// Returns whether the subsets named `sol` form a solution of the problem.
+    pub fn is_exact_cover(&self, sol: &[N]) -> bool {
+        sol.iter().all(|name| self.subsets.contains_key(name))
+            && self.violated_constraints(sol).is_empty()
+    }
+
+    /// Solves the problem on the current thread with a low-level [`Callback`],
+    /// e.g. to prune the search or to count the solutions without collecting them.
+    /// 
+    /// The callback is given the matrix generated by [`Matrix::from_problem`],
+    /// so it can stop the search by [`Matrix::abort`].
+    /// It returns the subset names to decode the solutions,
+    /// where `names[row - 1]` is the subset of row `row` (1-based).
+    /// It returns an error if the problem is malformed (see [`validate`](Problem::validate)).
+    pub fn solve_with<C: Callback<Matrix>>(&self, callback: &mut C) -> Result<Vec<N>, ProblemError> {
+        let (mut mat, names) = Matrix::from_problem(self)?;
+        mat.solve(callback);
+        Ok(names)
+    }
+}
+
+
+/// A builder of [`Problem`], which validates the problem when it is built.
+/// 
+/// # Example
+/// 
+/// ```
+/// use exact_cover::ProblemBuilder;
+/// 
+/// let prob = ProblemBuilder::new()
+///     .exact_constraint(1)
+///     .constraint(2, 0, 1)
+///     .subset("A", vec![1, 2])
+///     .subset("B", vec![1])
+///     .build()
+///     .unwrap();
+/// assert_eq!(prob.subsets().len(), 2);
+/// ```
+pub struct ProblemBuilder<N: Value, E: Value> {
+    problem: Problem<N, E>,
+    infer_constraints: bool,
+}
+
+impl<N: Value, E: Value> Default for ProblemBuilder<N, E> {
+    fn default() -> ProblemBuilder<N, E> {
+        ProblemBuilder {
+            problem: Problem::default(),
+            infer_constraints: false,
+        }
+    }
 }
 
+impl<N: Value, E: Value> ProblemBuilder<N, E> {
+    /// Creates a builder of an empty problem.
+    pub fn new() -> ProblemBuilder<N, E> {
+        ProblemBuilder::default()
+    }
+
+    /// Adds a constraint with a multiplicity range (see [`Problem::add_constraint`]).
+    pub fn constraint(mut self, elem: E, min: usize, max: usize) -> ProblemBuilder<N, E> {
+        self.problem.add_constraint(elem, min, max);
+        self
+    }
+
+    /// Adds a constraint that has to be covered exactly once.
+    pub fn exact_constraint(mut self, elem: E) -> ProblemBuilder<N, E> {
+        self.problem.add_exact_constraint(elem);
+        self
+    }
+
+    /// Adds a subset (see [`Problem::add_subset`]).
+    pub fn subset(mut self, name: N, subset: Vec<E>) -> ProblemBuilder<N, E> {
+        self.problem.add_subset(name, subset);
+        self
+    }
+
+    /// Sets whether the elements of the subsets that are not declared as constraints
+    /// become exact constraints when the problem is built (see [`Problem::infer_exact_constraints`]).
+    /// 
+    /// Otherwise, such an element is an error of [`build`](ProblemBuilder::build).
+    pub fn infer_constraints(mut self, enabled: bool) -> ProblemBuilder<N, E> {
+        self.infer_constraints = enabled;
+        self
+    }
+
+    /// Builds the problem, and validates it with [`Problem::validate`].
+    pub fn build(self) -> Result<Problem<N, E>, ProblemError> {
+        let mut problem = self.problem;
+        if self.infer_constraints { problem.infer_exact_constraints(); }
+        problem.validate()?;
+        Ok(problem)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -83,4 +722,494 @@ mod tests {
         prob.add_subset("E", vec![2, 7]);
         prob.add_subset("F", vec![4, 5, 7]);
     }
+
+    #[test]
+    fn problem_can_be_built() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let builder = || ProblemBuilder::new()
+            .subset("A", vec![1, 2, 3])
+            .subset("B", vec![1])
+            .subset("C", vec![2])
+            .subset("D", vec![3])
+            .subset("E", vec![1, 2])
+            .subset("F", vec![2, 3]);
+
+        let built = builder().exact_constraint(1).exact_constraint(2).exact_constraint(3).build();
+        assert_eq!(built, Ok(prob.clone()));
+        assert_eq!(builder().infer_constraints(true).build(), Ok(prob));
+        assert_eq!(builder().exact_constraint(1).build(), Err(ProblemError::UnknownElement { subset: 0, index: 1 }));
+
+        // Declared constraints keep their multiplicities
+        let inferred = builder().constraint(2, 0, 2).infer_constraints(true).build().unwrap();
+        assert!(inferred.constraints().iter().eq([(&2, &(0, 2)), (&1, &(1, 1)), (&3, &(1, 1))]));
+    }
+
+    #[test]
+    fn problem_can_be_collected_from_subsets() {
+        let mut manual = Problem::default();
+        manual.add_exact_constraints(1..=3);
+        manual.add_subset("A", vec![1, 2, 3]);
+        manual.add_subset("B", vec![1]);
+        manual.add_subset("C", vec![2]);
+        manual.add_subset("D", vec![3]);
+        manual.add_subset("E", vec![1, 2]);
+        manual.add_subset("F", vec![2, 3]);
+
+        let subsets = || manual.subsets().iter().map(|(name, subset)| (*name, subset.clone()));
+        let mut collected: Problem<_, _> = subsets().collect();
+        assert!(collected.constraints().is_empty());
+        collected.add_exact_constraints(1..=3);
+        assert_eq!(collected, manual);
+        assert_eq!(Problem::from_subsets(subsets()), manual);
+    }
+
+    #[test]
+    fn exact_constraints_can_be_inferred() {
+        use crate::Solver;
+
+        let mut explicit = Problem::default();
+        explicit.add_exact_constraints(1..=7);
+        explicit.add_constraint(8, 0, 1);
+        let mut inferred = Problem::default();
+        inferred.add_constraint(8, 0, 1);
+
+        for prob in [&mut explicit, &mut inferred] {
+            prob.add_subset("A", vec![3, 5, 6]);
+            prob.add_subset("B", vec![1, 4, 7]);
+            prob.add_subset("C", vec![2, 3, 6, 8]);
+            prob.add_subset("D", vec![1, 4]);
+            prob.add_subset("E", vec![2, 7]);
+            prob.add_subset("F", vec![4, 5, 7, 8]);
+        }
+        inferred.infer_exact_constraints();
+
+        assert!(inferred.constraints().keys().eq(&[8, 3, 5, 6, 1, 4, 7, 2]));
+        assert_eq!(inferred.constraints()[&8], (0, 1));
+
+        // The constraint order may change the order of the subsets in a solution
+        let solve = |prob| -> Vec<Vec<&str>> {
+            let mut solutions = Solver::new(prob).solve_blocking().unwrap();
+            for sol in &mut solutions { sol.sort_unstable(); }
+            solutions.sort();
+            solutions
+        };
+        assert_eq!(solve(inferred), solve(explicit));
+    }
+
+    #[test]
+    fn subsets_of_any_size_are_solved() {
+        use crate::Solver;
+
+        // The intervals of 1..=10 tile it in as many ways as the compositions of 10
+        let intervals = (1..=10).flat_map(|a| (a..=10).map(move |b| ((a, b), (a..=b).collect::<Vec<_>>())));
+        let mut prob = Problem::from_subsets(intervals);
+        assert_eq!(prob.subset(&(1, 10)).map(<[_]>::len), Some(10));
+        assert_eq!(Solver::new(prob.clone()).solve_blocking().unwrap().len(), 512);
+
+        assert_eq!(prob.remove_subset(&(1, 10)), Some((1..=10).collect()));
+        assert_eq!(prob.remove_subset(&(2, 3)), Some(vec![2, 3]));
+        // Without the tilings using 1..=10 at once, or 2..=3 after 1 and before the 64 tilings of 4..=10
+        assert_eq!(Solver::new(prob).solve_blocking().unwrap().len(), 512 - 1 - 64);
+    }
+
+    #[test]
+    fn subsets_and_constraints_can_be_iterated() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraint(3);
+        prob.add_constraint(1, 0, 2);
+        prob.add_exact_constraint(2);
+        prob.add_subset("B", vec![3, 1]);
+        prob.add_subset("A", vec![2]);
+        prob.add_subset("C", vec![1, 2]);
+
+        assert!(prob.subset_names().eq(&["B", "A", "C"]));
+        assert_eq!(prob.subset(&"B"), Some(&[3, 1][..]));
+        assert_eq!(prob.subset(&"X"), None);
+        assert!(prob.constraint_ranges().eq([(&3, (1, 1)), (&1, (0, 2)), (&2, (1, 1))]));
+
+        prob.remove_subset(&"A");
+        prob.remove_constraint(&3);
+        assert!(prob.subset_names().eq(&["B", "C"]));
+        assert_eq!(prob.subset(&"B"), Some(&[1][..]));
+        assert!(prob.constraint_ranges().map(|(e, _)| *e).eq([1, 2]));
+    }
+
+    #[test]
+    fn subsets_and_constraints_can_be_removed() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=4);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![2, 3, 4]);
+        prob.add_subset("C", vec![3]);
+        prob.add_subset("D", vec![4]);
+
+        assert_eq!(prob.remove_subset(&"B"), Some(vec![2, 3, 4]));
+        assert_eq!(prob.remove_subset(&"B"), None);
+        assert!(prob.subsets().keys().eq(&["A", "C", "D"]));
+
+        assert_eq!(prob.remove_constraint(&2), Some((1, 1)));
+        assert_eq!(prob.remove_constraint(&2), None);
+        assert!(prob.constraints().keys().eq(&[1, 3, 4]));
+        assert_eq!(prob.subsets()["A"], vec![1]);
+
+        assert!(prob.is_exact_cover(&["A", "C", "D"]));
+    }
+
+    #[test]
+    fn constraints_can_be_sorted() {
+        use crate::Solver;
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+        let solutions = Solver::new(prob.clone()).solve_blocking().unwrap();
+        assert_eq!(solutions, vec![vec!["A"], vec!["B", "C", "D"], vec!["B", "F"], vec!["E", "D"]]);
+
+        prob.sort_constraints_by(|e1, _, e2, _| e2.cmp(e1));
+        assert!(prob.constraints().keys().eq(&[3, 2, 1]));
+        let sorted = Solver::new(prob).solve_blocking().unwrap();
+        assert_ne!(sorted, solutions);
+
+        // The same solutions in a different order
+        let as_sets = |solutions: Vec<Vec<&'static str>>| {
+            let mut sets: Vec<_> = solutions.into_iter()
+                .map(|mut sol| { sol.sort_unstable(); sol })
+                .collect();
+            sets.sort_unstable();
+            sets
+        };
+        assert_eq!(as_sets(sorted), as_sets(solutions));
+    }
+
+    #[test]
+    fn disjoint_problems_can_be_merged() {
+        let mut base = Problem::default();
+        base.add_exact_constraints(1..=2);
+        base.add_subset("A", vec![1, 2]);
+        base.add_subset("B", vec![1]);
+        base.force_subset("B");
+
+        let mut extra = Problem::default();
+        extra.add_constraint(3, 0, 2);
+        extra.add_secondary_constraint(4);
+        extra.add_weighted_subset("C", vec![3], 5);
+        extra.add_colored_subset("D", vec![2, 3], vec![(4, 1)]);
+
+        let merged = base.clone().union(extra).unwrap();
+        assert!(merged.constraint_ranges().eq([(&1, (1, 1)), (&2, (1, 1)), (&3, (0, 2)), (&4, (0, 1))]));
+        assert!(merged.secondary_constraints().iter().eq(&[4]));
+        assert!(merged.subset_names().eq(&["A", "B", "C", "D"]));
+        assert!(merged.forced_subsets().iter().eq(&["B"]));
+        assert_eq!(merged.cost(&"C"), 5);
+        assert_eq!(merged.color(&"D", &4), Some(1));
+        assert!(merged.validate().is_ok());
+        assert!(merged.is_exact_cover(&["B", "D"]));
+
+        assert_eq!(base.clone().union(Problem::default()), Ok(base));
+    }
+
+    #[test]
+    fn union_conflicts_are_reported() {
+        let mut base = Problem::default();
+        base.add_constraint(1, 0, 2);
+        base.add_constraint(2, 1, 3);
+        base.add_subset("A", vec![1, 2]);
+
+        // Overlapping ranges are intersected
+        let mut tighter = Problem::default();
+        tighter.add_constraint(2, 2, 5);
+        tighter.add_subset("B", vec![2]);
+        let merged = base.clone().union(tighter).unwrap();
+        assert!(merged.constraint_ranges().eq([(&1, (0, 2)), (&2, (2, 3))]));
+
+        let mut disjoint = Problem::default();
+        disjoint.add_constraint(1, 3, 4);
+        assert_eq!(base.clone().union(disjoint), Err(UnionError::ConflictingConstraint(1)));
+
+        let mut secondary = Problem::default();
+        secondary.add_secondary_constraint(1);
+        assert_eq!(base.clone().union(secondary), Err(UnionError::ConflictingConstraint(1)));
+
+        let mut clash = Problem::default();
+        clash.add_constraint(1, 0, 2);
+        clash.add_subset("A", vec![1]);
+        assert_eq!(base.union(clash), Err(UnionError::DuplicateSubset("A")));
+    }
+
+    #[test]
+    fn duplicate_subset_names_can_be_rejected() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        assert_eq!(prob.try_add_subset("A", vec![1, 2]), Ok(()));
+        prob.add_weighted_subset("B", vec![3], 2);
+
+        // The existing subset is kept
+        assert_eq!(prob.try_add_subset("A", vec![3]), Err(DuplicateName("A")));
+        assert_eq!(prob.subset(&"A"), Some(&[1, 2][..]));
+        assert_eq!(DuplicateName("A").to_string(), r#"Duplicate subset name "A"."#);
+
+        // The existing subset is replaced along with its cost
+        prob.add_subset("B", vec![2, 3]);
+        assert_eq!(prob.subset(&"B"), Some(&[2, 3][..]));
+        assert_eq!(prob.cost(&"B"), 1);
+        assert_eq!(prob.subsets().len(), 2);
+    }
+
+    #[test]
+    fn subsets_can_have_costs() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_weighted_subset("A", vec![1, 2], 3);
+        prob.add_subset("B", vec![1]);
+        assert_eq!((prob.cost(&"A"), prob.cost(&"B")), (3, 1));
+
+        prob.add_subset("A", vec![1, 2]);
+        assert_eq!(prob.cost(&"A"), 1);
+    }
+
+    #[test]
+    fn optional_constraint_may_be_left_uncovered() {
+        use crate::Solver;
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_optional_constraint(3);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![1, 2, 3]);
+        prob.add_subset("C", vec![1]);
+        prob.add_subset("D", vec![2, 3]);
+        prob.add_subset("E", vec![3]);
+        assert_eq!(prob.constraints()[&3], (0, 1));
+        assert!(prob.secondary_constraints().is_empty());
+
+        let mut solutions = Solver::new(prob).solve_blocking().unwrap();
+        for sol in &mut solutions { sol.sort_unstable(); }
+        solutions.sort();
+        // Only ["A"] leaves 3 uncovered
+        assert_eq!(solutions, vec![vec!["A"], vec!["A", "E"], vec!["B"], vec!["C", "D"]]);
+    }
+
+    #[test]
+    fn minimum_set_cover_can_be_found() {
+        use crate::Solver;
+
+        let mut prob = Problem::default();
+        for e in 1..=5 { prob.add_cover_constraint(e, 1); }
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![2, 4]);
+        prob.add_subset("C", vec![3, 4]);
+        prob.add_subset("D", vec![4, 5]);
+        prob.add_subset("E", vec![1, 5]);
+        assert!(prob.unreachable_maximums().is_empty());
+
+        // Every subset can be selected at once
+        assert!(prob.is_exact_cover(&["A", "B", "C", "D", "E"]));
+        assert!(!prob.is_exact_cover(&["A", "B", "C"]));
+
+        let (mut sol, cost) = Solver::new(prob).solve_minimum().unwrap().unwrap();
+        sol.sort_unstable();
+        assert_eq!((sol, cost), (vec!["A", "D"], 2));
+    }
+
+    #[test]
+    fn problem_can_be_validated() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![3]);
+        assert_eq!(prob.validate(), Ok(()));
+
+        prob.add_subset("C", vec![2, 4, 3]);
+        assert_eq!(prob.validate(), Err(ProblemError::UnknownElement { subset: 2, index: 1 }));
+
+        prob.add_subset("A", vec![]);
+        assert_eq!(prob.validate(), Err(ProblemError::EmptySubset { subset: 0 }));
+    }
+
+    #[test]
+    fn violated_constraints_can_be_found() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_constraint(4, 0, 1);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![2, 3, 4]);
+        prob.add_subset("C", vec![3]);
+
+        assert!(prob.is_exact_cover(&["A", "C"]));
+        assert_eq!(prob.violated_constraints(&["A", "B"]), vec![2]);
+        assert_eq!(prob.violated_constraints(&["B"]), vec![1]);
+        assert!(!prob.is_exact_cover(&["A", "C", "X"]));
+    }
+
+    #[test]
+    fn problem_can_be_solved_with_callback() {
+        // Collects the first `limit` solutions and aborts the search
+        struct Limit { limit: usize, solutions: Vec<Vec<usize>> }
+
+        impl Callback<Matrix> for Limit {
+            fn on_solution(&mut self, sol: Vec<usize>, mat: &mut Matrix) {
+                self.solutions.push(sol);
+                if self.solutions.len() == self.limit { mat.abort(); }
+            }
+        }
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let mut callback = Limit { limit: 2, solutions: vec![] };
+        let names = prob.solve_with(&mut callback).unwrap();
+        let solutions: Vec<Vec<_>> = callback.solutions.iter()
+            .map(|sol| sol.iter().map(|&row| names[row - 1]).collect())
+            .collect();
+        assert_eq!(solutions, vec![vec!["A"], vec!["B", "C", "D"]]);
+
+        prob.add_subset("G", vec![4]);
+        assert_eq!(prob.solve_with(&mut ()), Err(ProblemError::UnknownElement { subset: 6, index: 0 }));
+    }
+
+    #[test]
+    fn forced_subsets_are_validated() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_constraint(4, 0, 2);
+        prob.add_subset("A", vec![1, 4]);
+        prob.add_subset("B", vec![2, 4]);
+        prob.add_subset("C", vec![2, 3]);
+
+        assert!(!prob.force_subset("X"));
+        assert!(prob.force_subset("A") && prob.force_subset("B"));
+        assert_eq!(prob.validate(), Ok(()));
+
+        prob.force_subset("C");
+        assert_eq!(prob.validate(), Err(ProblemError::ConflictingSubset { subset: 2 }));
+
+        prob.remove_subset(&"B");
+        assert!(prob.forced_subsets().iter().eq(&["A", "C"]));
+        assert_eq!(prob.validate(), Ok(()));
+    }
+
+    #[test]
+    fn secondary_constraints_can_be_colored() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_secondary_constraint(3);
+        prob.add_colored_subset("A", vec![1], vec![(3, 7)]);
+        prob.add_colored_subset("B", vec![2], vec![(3, 7)]);
+        prob.add_colored_subset("C", vec![2], vec![(3, 8)]);
+        prob.add_subset("D", vec![2, 3]);
+        assert_eq!(prob.subsets()["A"], vec![1, 3]);
+        assert_eq!((prob.color(&"A", &3), prob.color(&"A", &1), prob.color(&"D", &3)), (Some(7), None, None));
+        assert_eq!(prob.validate(), Ok(()));
+
+        assert!(prob.is_exact_cover(&["A", "B"]));
+        assert_eq!(prob.violated_constraints(&["A", "C"]), vec![3]);
+        assert_eq!(prob.violated_constraints(&["A", "D"]), vec![3]);
+        assert_eq!(prob.violated_constraints(&["D"]), vec![1]);
+
+        prob.force_subset("A");
+        prob.force_subset("C");
+        assert_eq!(prob.validate(), Err(ProblemError::ConflictingSubset { subset: 2 }));
+
+        prob.add_colored_subset("E", vec![2], vec![(1, 7)]);
+        assert_eq!(prob.validate(), Err(ProblemError::ColoredPrimaryConstraint { subset: 4, index: 1 }));
+
+        prob.remove_constraint(&3);
+        assert!(prob.secondary_constraints().is_empty());
+        assert_eq!(prob.color(&"A", &3), None);
+    }
+
+    #[test]
+    fn invalid_multiplicities_are_detected() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraint(1);
+        prob.add_constraint(2, 2, 1);
+        prob.add_subset("A", vec![1, 2]);
+        assert_eq!(prob.validate(), Err(ProblemError::InvalidMultiplicity { constraint: 1 }));
+
+        prob.add_constraint(2, 0, 3);
+        prob.add_constraint(3, 0, 1);
+        prob.add_secondary_constraint(4);
+        prob.add_subset("B", vec![2, 3]);
+        assert_eq!(prob.validate(), Ok(()));
+        assert_eq!(prob.unreachable_maximums(), vec![2]);
+
+        prob.add_subset("C", vec![2]);
+        assert!(prob.unreachable_maximums().is_empty());
+    }
+
+    #[test]
+    fn uncoverable_constraints_can_be_found() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_constraint(4, 0, 1);
+        prob.add_constraint(5, 2, 2);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![2, 5]);
+        prob.add_subset("C", vec![1, 5]);
+        assert_eq!(prob.uncoverable_constraints(), vec![3]);
+
+        prob.remove_subset(&"C");
+        prob.add_subset("D", vec![3]);
+        assert_eq!(prob.uncoverable_constraints(), vec![5]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn problem_can_be_serialized() {
+        use crate::{Solver, SolverEvent};
+
+        fn solve(prob: Problem<String, i32>) -> Vec<Vec<String>> {
+            let mut solver = Solver::new(prob);
+            let mut solutions = vec![];
+            solver.run().unwrap();
+
+            for event in solver {
+                if let SolverEvent::SolutionFound(sol) = event {
+                    solutions.push(sol);
+                }
+            }
+            solutions
+        }
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A".to_string(), vec![1, 2, 3]);
+        prob.add_subset("B".to_string(), vec![1]);
+        prob.add_subset("C".to_string(), vec![2]);
+        prob.add_subset("D".to_string(), vec![3]);
+        prob.add_subset("E".to_string(), vec![1, 2]);
+        prob.add_subset("F".to_string(), vec![2, 3]);
+
+        let json = serde_json::to_string(&prob).unwrap();
+        let deserialized: Problem<String, i32> = serde_json::from_str(&json).unwrap();
+
+        assert!(deserialized.subsets().keys().eq(prob.subsets().keys()));
+        assert!(deserialized.constraints().keys().eq(prob.constraints().keys()));
+        assert_eq!(solve(deserialized), solve(prob.clone()));
+
+        // The forced subsets are not checked while deserializing
+        prob.force_subset("B".to_string());
+        let json = serde_json::to_string(&prob).unwrap().replace(r#"["B"]"#, r#"["B","X"]"#);
+        let deserialized: Problem<String, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.validate(), Err(ProblemError::UnknownForcedSubset { forced: 1 }));
+    }
 }
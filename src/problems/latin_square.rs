@@ -0,0 +1,134 @@
+//! A Latin square completion problem.
+
+use crate::problem::Problem;
+
+/// A symbol placed in a cell, represented as `(row, col, symbol)`.
+/// It is used as a subset name of [`Problem`] instance.
+pub type Placement = (usize, usize, usize);
+
+/// An exact cover constraint for Latin square completion.
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(test, derive(Debug))]
+pub enum LatinSquareConstraint {
+    /// The cell `(row, col)` contains a symbol.
+    Cell(usize, usize),
+    /// The row contains the symbol.
+    Row(usize, usize),
+    /// The column contains the symbol.
+    Col(usize, usize),
+}
+
+/// A partially filled n x n Latin square, whose symbols are `1..=n`.
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct LatinSquare {
+    grid: Vec<Vec<usize>>,
+}
+
+impl LatinSquare {
+    /// Creates a new problem from a square grid of symbols, where `0` represents a blank cell.
+    pub fn from_grid(grid: Vec<Vec<usize>>) -> LatinSquare {
+        let n = grid.len();
+        assert!(grid.iter().all(|row| row.len() == n), "The grid must be square");
+        assert!(grid.iter().flatten().all(|&s| s <= n), "Symbols must be in 0..=n");
+        LatinSquare { grid }
+    }
+
+    /// Returns the grid, where `0` represents a blank cell.
+    pub fn grid(&self) -> &Vec<Vec<usize>> { &self.grid }
+    /// Returns the order `n` of the square.
+    pub fn size(&self) -> usize { self.grid.len() }
+
+    /// Generates an exact cover problem instance ([`Problem`]).
+    /// 
+    /// A prefilled cell only has a single subset which places the given symbol,
+    /// so the placement is forced in every solution.
+    pub fn generate_problem(&self) -> Problem<Placement, LatinSquareConstraint> {
+        let n = self.size();
+        let mut prob = Problem::default();
+
+        for i in 0..n {
+            for j in 0..n {
+                prob.add_exact_constraint(LatinSquareConstraint::Cell(i, j));
+            }
+        }
+        for i in 0..n {
+            for s in 1..=n {
+                prob.add_exact_constraint(LatinSquareConstraint::Row(i, s));
+                prob.add_exact_constraint(LatinSquareConstraint::Col(i, s));
+            }
+        }
+
+        for row in 0..n {
+            for col in 0..n {
+                let symbols = match self.grid[row][col] {
+                    0 => 1..=n,
+                    s => s..=s,
+                };
+                for s in symbols {
+                    prob.add_subset((row, col, s), vec![
+                        LatinSquareConstraint::Cell(row, col),
+                        LatinSquareConstraint::Row(row, s),
+                        LatinSquareConstraint::Col(col, s),
+                    ]);
+                }
+            }
+        }
+
+        prob
+    }
+
+    /// Decodes a solution of the generated problem into a completed grid.
+    pub fn decode(&self, sol: &[Placement]) -> Vec<Vec<usize>> {
+        let mut grid = self.grid.clone();
+        for &(row, col, s) in sol {
+            grid[row][col] = s;
+        }
+        grid
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solver;
+
+    fn completions(square: &LatinSquare) -> Vec<Vec<Vec<usize>>> {
+        Solver::new(square.generate_problem()).solve_blocking().unwrap()
+            .iter()
+            .map(|sol| square.decode(sol))
+            .collect()
+    }
+
+    #[test]
+    fn partial_square_can_be_completed() {
+        let square = LatinSquare::from_grid(vec![
+            vec![1, 0, 0, 0],
+            vec![0, 2, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 4],
+        ]);
+
+        let solutions = completions(&square);
+        assert_eq!(solutions.len(), 8);
+
+        for grid in solutions {
+            assert_eq!((grid[0][0], grid[1][1], grid[3][3]), (1, 2, 4));
+            for i in 0..4 {
+                let mut row = grid[i].clone();
+                let mut col: Vec<_> = grid.iter().map(|row| row[i]).collect();
+                row.sort_unstable();
+                col.sort_unstable();
+                assert_eq!(row, vec![1, 2, 3, 4]);
+                assert_eq!(col, vec![1, 2, 3, 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn blank_square_has_all_latin_squares() {
+        let square = LatinSquare::from_grid(vec![vec![0; 4]; 4]);
+        assert_eq!(completions(&square).len(), 576);
+    }
+}
@@ -0,0 +1,101 @@
+//! A perfect matching problem on a graph.
+
+use crate::problem::{Problem, Value};
+
+/// An undirected edge between two vertices.
+/// It is used as a subset name of [`Problem`] instance.
+pub type Edge<V> = (V, V);
+
+/// A graph to find perfect matchings of,
+/// i.e. sets of edges that cover every vertex exactly once.
+///
+/// The vertices are the constraints and the edges are the subsets of the exact cover problem.
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct PerfectMatching<V: Value> {
+    vertices: Vec<V>,
+    edges: Vec<Edge<V>>,
+}
+
+impl<V: Value> PerfectMatching<V> {
+    /// Creates a new problem from the vertices and the edges between them.
+    ///
+    /// A loop, i.e. an edge from a vertex to itself, can never be in a matching.
+    pub fn from_edges(vertices: Vec<V>, edges: Vec<Edge<V>>) -> PerfectMatching<V> {
+        assert!(
+            edges.iter().all(|(u, v)| vertices.contains(u) && vertices.contains(v)),
+            "Edges must connect the given vertices"
+        );
+        PerfectMatching { vertices, edges }
+    }
+
+    /// Returns the vertices.
+    pub fn vertices(&self) -> &Vec<V> { &self.vertices }
+    /// Returns the edges.
+    pub fn edges(&self) -> &Vec<Edge<V>> { &self.edges }
+
+    /// Generates an exact cover problem instance ([`Problem`]).
+    pub fn generate_problem(&self) -> Problem<Edge<V>, V> {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(self.vertices.iter().cloned());
+
+        for (u, v) in &self.edges {
+            if u == v { continue }
+            prob.add_subset((u.clone(), v.clone()), vec![u.clone(), v.clone()]);
+        }
+
+        prob
+    }
+
+    /// Decodes a solution of the generated problem into the chosen edges,
+    /// in the order they were given.
+    pub fn decode(&self, sol: &[Edge<V>]) -> Vec<Edge<V>> {
+        self.edges.iter()
+            .filter(|edge| sol.contains(edge))
+            .cloned()
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solver;
+
+    fn matchings<V: Value>(graph: &PerfectMatching<V>) -> Vec<Vec<Edge<V>>> {
+        Solver::new(graph.generate_problem()).solve_blocking().unwrap()
+            .iter()
+            .map(|sol| graph.decode(sol))
+            .collect()
+    }
+
+    #[test]
+    fn cycle_has_two_perfect_matchings() {
+        let graph = PerfectMatching::from_edges(
+            vec!['a', 'b', 'c', 'd'],
+            vec![('a', 'b'), ('b', 'c'), ('c', 'd'), ('d', 'a')],
+        );
+
+        let mut solutions = matchings(&graph);
+        solutions.sort();
+        assert_eq!(solutions, vec![
+            vec![('a', 'b'), ('c', 'd')],
+            vec![('b', 'c'), ('d', 'a')],
+        ]);
+    }
+
+    #[test]
+    fn complete_graph_matchings_are_counted() {
+        // K_n has (n - 1)!! perfect matchings for even n, and none for odd n
+        let complete = |n: usize| {
+            let edges = (0..n).flat_map(|u| (u + 1..n).map(move |v| (u, v))).collect();
+            PerfectMatching::from_edges((0..n).collect(), edges)
+        };
+        assert_eq!(matchings(&complete(5)).len(), 0);
+        assert_eq!(matchings(&complete(6)).len(), 15);
+
+        let with_loop = PerfectMatching::from_edges(vec![0, 1], vec![(0, 0), (0, 1), (1, 1)]);
+        assert_eq!(matchings(&with_loop), vec![vec![(0, 1)]]);
+    }
+}
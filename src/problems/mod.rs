@@ -1,3 +1,8 @@
 //! Various exact cover problem instances.
 
+pub mod latin_square;
+pub mod matching;
+pub mod polyhex;
+pub mod polycube;
 pub mod polyomino;
+pub mod sudoku;
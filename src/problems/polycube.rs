@@ -0,0 +1,336 @@
+//! A polycube packing problem, the 3D analog of [polyomino packing](super::polyomino).
+
+use indexmap::{IndexMap, IndexSet};
+use crate::problem::{Problem, Value};
+use crate::problems::polyomino::{Cell, InvalidPieceError};
+use crate::vector::Vector3D;
+
+// Orientation
+// ===========
+
+/// An orientation of a piece, one of the 24 rotations of a cube.
+/// 
+/// The piece is first rotated around the z-axis by `(spin * 90)` degrees,
+/// then turned so that the positive z-axis faces toward `face`.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Orientation {
+    pub face: usize, // 0..6
+    pub spin: i32, // 0..4
+}
+
+impl Orientation {
+    /// Returns all 24 orientations.
+    pub fn all() -> impl Iterator<Item = Orientation> {
+        (0..6).flat_map(|face| (0..4).map(move |spin| Orientation { face, spin }))
+    }
+
+    /// Applies the orientation to a vector.
+    pub fn apply(&self, v: Vector3D) -> Vector3D {
+        let v = v.rotate_z(self.spin);
+        match self.face {
+            0 => v,               // +z
+            1 => v.rotate_x(1),   // -y
+            2 => v.rotate_x(2),   // -z
+            3 => v.rotate_x(3),   // +y
+            4 => v.rotate_y(1),   // +x
+            5 => v.rotate_y(3),   // -x
+            _ => panic!("Orientation face must be in 0..6"),
+        }
+    }
+}
+
+
+// Polycube
+// ========
+
+/// A polycube piece, possibly with disconnected cells.
+/// 
+/// The coordinates are normalized upon creation,
+/// so it does not contain translation information.
+#[derive(PartialEq, Eq, Hash, Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Polycube {
+    cells: Vec<Vector3D>,
+    size: Vector3D,
+}
+
+impl Polycube {
+    /// Creates a new `Polycube` from a list of cell positions.
+    /// 
+    /// The coordinates are normalized upon creation,
+    /// i.e., the minimums of x/y/z coordinates are set to 0.
+    /// It returns an error if the given list is empty or contains the same cell twice.
+    pub fn new(cells: &[Vector3D]) -> Result<Polycube, InvalidPieceError> {
+        if cells.is_empty() { return Err(InvalidPieceError::Blank) }
+
+        let min = Vector3D {
+            x: cells.iter().map(|c| c.x).min().unwrap(),
+            y: cells.iter().map(|c| c.y).min().unwrap(),
+            z: cells.iter().map(|c| c.z).min().unwrap(),
+        };
+        let max = Vector3D {
+            x: cells.iter().map(|c| c.x).max().unwrap(),
+            y: cells.iter().map(|c| c.y).max().unwrap(),
+            z: cells.iter().map(|c| c.z).max().unwrap(),
+        };
+
+        let mut normalized_cells: Vec<_> = cells.iter().map(|&c| c - min).collect();
+        normalized_cells.sort();
+        if normalized_cells.windows(2).any(|w| w[0] == w[1]) { return Err(InvalidPieceError::DuplicateCell) }
+
+        Ok(Polycube {
+            cells: normalized_cells,
+            size: max - min + Vector3D { x: 1, y: 1, z: 1 },
+        })
+    }
+
+    /// Returns the list of cells in the piece.
+    pub fn cells(&self) -> &Vec<Vector3D> { &self.cells }
+    /// Returns the size of the bounding box.
+    pub fn size(&self) -> Vector3D { self.size }
+
+    /// Orients the piece according to the given orientation.
+    pub fn orient(&self, orientation: Orientation) -> Polycube {
+        let oriented: Vec<_> = self.cells.iter()
+            .map(|&c| orientation.apply(c))
+            .collect();
+
+        Polycube::new(&oriented).unwrap()
+    }
+
+    /// Returns possible orientations of the piece without duplication.
+    pub fn unique_orientations(&self) -> Vec<Orientation> {
+        let mut pieces = IndexSet::new();
+        let mut res = Vec::new();
+
+        for o in Orientation::all() {
+            let piece = self.orient(o);
+            if !pieces.contains(&piece) {
+                pieces.insert(piece);
+                res.push(o);
+            }
+        }
+        res
+    }
+
+    /// Returns the list of cells after translation.
+    pub fn translated_cells(&self, trans: Vector3D) -> Vec<Vector3D> {
+        self.cells.iter()
+            .map(|&c| c + trans)
+            .collect()
+    }
+}
+
+
+// Board
+// ========
+
+/// A 3D board to fit the pieces in.
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Board {
+    cells: Vec<Vec<Vec<Cell>>>,
+    size: Vector3D,
+}
+
+impl Board {
+    /// Creates a new board from a 3D list of cells, indexed by `cells[z][y][x]`.
+    pub fn new(cells: Vec<Vec<Vec<Cell>>>) -> Board {
+        assert!(!cells.is_empty() && !cells[0].is_empty());
+
+        Board {
+            size: Vector3D {
+                z: cells.len() as i32,
+                y: cells[0].len() as i32,
+                x: cells[0][0].len() as i32,
+            },
+            cells,
+        }
+    }
+
+    /// Returns a 3D list of cells, indexed by `cells[z][y][x]`.
+    pub fn cells(&self) -> &Vec<Vec<Vec<Cell>>> { &self.cells }
+    /// Returns the size of the board.
+    pub fn size(&self) -> Vector3D { self.size }
+
+    /// Returns whether the given piece can fit in the board
+    /// with specified orientation and translation.
+    pub fn piece_fits(
+        &self,
+        piece: &Polycube,
+        orien: Orientation,
+        trans: Vector3D,
+    ) -> bool {
+        for c in piece.orient(orien).translated_cells(trans) {
+            if self.cell(c).unwrap_or(&Cell::Empty) == &Cell::Empty { return false }
+        }
+        true
+    }
+
+    fn cell(&self, Vector3D { x, y, z }: Vector3D) -> Option<&Cell> {
+        self.cells.get(usize::try_from(z).ok()?)?
+            .get(usize::try_from(y).ok()?)?
+            .get(usize::try_from(x).ok()?)
+    }
+}
+
+
+// Problem
+// =========
+
+/// An identifier of a piece placed in a specified orientation and translation.
+/// It is used as a subset name of [`Problem`] instance.
+pub type CompoundName<N> = (N, Orientation, Vector3D);
+
+/// An exact cover constraint for polycube packing problem.
+#[derive(PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(test, derive(Debug))]
+pub enum CompoundConstraint<N> {
+    Piece(N),
+    Cell(Vector3D),
+}
+
+/// A polycube packing problem.
+#[cfg_attr(test, derive(Debug))]
+pub struct PolycubePacking<N: Value> {
+    board: Board,
+    pieces: IndexMap<N, Polycube>,
+    min: IndexMap<N, usize>,
+    max: IndexMap<N, usize>,
+}
+
+impl<N: Value> Default for PolycubePacking<N> {
+    fn default() -> PolycubePacking<N> {
+        PolycubePacking {
+            board: Default::default(),
+            pieces: Default::default(),
+            min: Default::default(),
+            max: Default::default(),
+        }
+    }
+}
+
+impl<N: Value> PolycubePacking<N> {
+    /// Creates an empty problem.
+    pub fn new() -> PolycubePacking<N> {
+        PolycubePacking::default()
+    }
+
+    /// Returns a reference to the board.
+    pub fn board(&self) -> &Board { &self.board }
+    /// Returns a mutable reference to the board.
+    pub fn board_mut(&mut self) -> &mut Board { &mut self.board }
+    /// Sets the board.
+    pub fn set_board(&mut self, board: Board) { self.board = board; }
+    /// Returns a reference to the pieces.
+    pub fn pieces(&self) -> &IndexMap<N, Polycube> { &self.pieces }
+
+    /// Adds a piece to the problem.
+    /// 
+    /// If the piece name already exists, it replaces the corresponding piece.
+    pub fn add_piece(&mut self, name: N, piece: Polycube) {
+        self.pieces.insert(name.clone(), piece);
+        self.min.insert(name.clone(), 1);
+        self.max.insert(name.clone(), 1);
+    }
+
+    /// Sets a piece's multiplicity range.
+    pub fn set_piece_range(&mut self, name: N, min: usize, max: usize) {
+        if self.pieces.contains_key(&name) {
+            self.min[&name] = min;
+            self.max[&name] = max;
+        }
+    }
+
+    /// Generates an exact cover problem instance ([`Problem`]).
+    pub fn generate_problem(&self) -> Problem<CompoundName<N>, CompoundConstraint<N>> {
+        let mut prob = Problem::<CompoundName<N>, CompoundConstraint<N>>::default();
+        let size = self.board.size;
+
+        // Piece constraints
+        for name in self.pieces.keys() {
+            prob.add_constraint(CompoundConstraint::Piece(name.clone()), self.min[name], self.max[name]);
+        }
+
+        // Cell contraints
+        for z in 0..size.z {
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    let c = Vector3D { x, y, z };
+                    match self.board.cell(c).unwrap() {
+                        Cell::Filled => prob.add_constraint(CompoundConstraint::Cell(c), 1, 1),
+                        Cell::Wildcard => prob.add_constraint(CompoundConstraint::Cell(c), 0, 1),
+                        Cell::Empty => (),
+                    }
+                }
+            }
+        }
+
+        // Subsets
+        for (name, piece) in &self.pieces {
+            for o in piece.unique_orientations() {
+                let p = piece.orient(o);
+                for z in 0..=(size.z - p.size.z) {
+                    for y in 0..=(size.y - p.size.y) {
+                        for x in 0..=(size.x - p.size.x) {
+                            let t = Vector3D { x, y, z };
+                            if !self.board.piece_fits(piece, o, t) { continue }
+
+                            let mut subset = vec![CompoundConstraint::Piece(name.clone())];
+                            subset.extend(p.translated_cells(t).into_iter().map(CompoundConstraint::Cell));
+                            prob.add_subset((name.clone(), o, t), subset);
+                        }
+                    }
+                }
+            }
+        }
+
+        prob
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solver;
+
+    fn v(x: i32, y: i32, z: i32) -> Vector3D { Vector3D { x, y, z } }
+
+    #[test]
+    fn orientations_are_distinct_rotations() {
+        let images: IndexSet<_> = Orientation::all()
+            .map(|o| (o.apply(v(1, 0, 0)), o.apply(v(0, 1, 0))))
+            .collect();
+        assert_eq!(images.len(), 24);
+    }
+
+    #[test]
+    fn unique_orientations_can_be_found() {
+        let unit = Polycube::new(&[v(0, 0, 0)]).unwrap();
+        assert_eq!(unit.unique_orientations().len(), 1);
+
+        let rod = Polycube::new(&[v(0, 0, 0), v(1, 0, 0), v(2, 0, 0)]).unwrap();
+        assert_eq!(rod.unique_orientations().len(), 3);
+
+        // A chiral Soma piece still has a 2-fold rotational symmetry.
+        let screw = Polycube::new(&[v(0, 0, 0), v(1, 0, 0), v(1, 1, 0), v(1, 1, 1)]).unwrap();
+        assert_eq!(screw.unique_orientations().len(), 12);
+
+        let chiral = Polycube::new(&[v(0, 0, 0), v(1, 0, 0), v(2, 0, 0), v(2, 1, 0), v(2, 1, 1)]).unwrap();
+        assert_eq!(chiral.unique_orientations().len(), 24);
+    }
+
+    #[test]
+    fn cube_can_be_filled_with_unit_cubes() {
+        let mut prob = PolycubePacking::new();
+        prob.set_board(Board::new(vec![vec![vec![Cell::Filled; 2]; 2]; 2]));
+        prob.add_piece("unit", Polycube::new(&[v(0, 0, 0)]).unwrap());
+        prob.set_piece_range("unit", 8, 8);
+
+        let solutions = Solver::new(prob.generate_problem()).solve_blocking().unwrap();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].len(), 8);
+    }
+}
@@ -0,0 +1,331 @@
+//! A polyhex packing problem, the hexagonal grid analog of [polyomino packing](super::polyomino).
+
+use indexmap::{IndexMap, IndexSet};
+use crate::problem::{Problem, Value};
+use crate::problems::polyomino::{Cell, InvalidPieceError};
+use crate::vector::Hex;
+
+// Orientation
+// ===========
+
+/// An orientation of a piece, one of the 6 rotations possibly after a reflection.
+///
+/// Reflection is applied first, then rotation.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Orientation {
+    pub rotation: i32, // 0..6
+    pub reflection: bool,
+}
+
+impl Orientation {
+    /// Returns all 12 orientations.
+    pub fn all() -> impl Iterator<Item = Orientation> {
+        [false, true].into_iter()
+            .flat_map(|reflection| (0..6).map(move |rotation| Orientation { rotation, reflection }))
+    }
+
+    /// Applies the orientation to a coordinate.
+    pub fn apply(&self, h: Hex) -> Hex {
+        let h = if self.reflection { h.reflect() } else { h };
+        h.rotate(self.rotation)
+    }
+}
+
+
+// Polyhex
+// =======
+
+/// A polyhex piece, possibly with disconnected cells.
+///
+/// The coordinates are normalized upon creation,
+/// so it does not contain translation information.
+#[derive(PartialEq, Eq, Hash, Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Polyhex {
+    cells: Vec<Hex>,
+}
+
+impl Polyhex {
+    /// Creates a new `Polyhex` from a list of cell positions.
+    ///
+    /// The coordinates are normalized upon creation,
+    /// i.e., the minimums of q/r coordinates are set to 0.
+    /// It returns an error if the given list is empty or contains the same cell twice.
+    pub fn new(cells: &[Hex]) -> Result<Polyhex, InvalidPieceError> {
+        if cells.is_empty() { return Err(InvalidPieceError::Blank) }
+
+        let min = Hex {
+            q: cells.iter().map(|c| c.q).min().unwrap(),
+            r: cells.iter().map(|c| c.r).min().unwrap(),
+        };
+
+        let mut normalized_cells: Vec<_> = cells.iter().map(|&c| c - min).collect();
+        normalized_cells.sort();
+        if normalized_cells.windows(2).any(|w| w[0] == w[1]) { return Err(InvalidPieceError::DuplicateCell) }
+
+        Ok(Polyhex { cells: normalized_cells })
+    }
+
+    /// Returns the list of cells in the piece.
+    pub fn cells(&self) -> &Vec<Hex> { &self.cells }
+    /// Returns the number of cells in the piece.
+    pub fn area(&self) -> usize { self.cells.len() }
+
+    /// Orients the piece according to the given orientation.
+    pub fn orient(&self, orientation: Orientation) -> Polyhex {
+        let oriented: Vec<_> = self.cells.iter()
+            .map(|&c| orientation.apply(c))
+            .collect();
+
+        Polyhex::new(&oriented).unwrap()
+    }
+
+    /// Returns possible orientations of the piece without duplication.
+    pub fn unique_orientations(&self) -> Vec<Orientation> {
+        let mut pieces = IndexSet::new();
+        let mut res = Vec::new();
+
+        for o in Orientation::all() {
+            let piece = self.orient(o);
+            if !pieces.contains(&piece) {
+                pieces.insert(piece);
+                res.push(o);
+            }
+        }
+        res
+    }
+
+    /// Returns the list of cells after translation.
+    pub fn translated_cells(&self, trans: Hex) -> Vec<Hex> {
+        self.cells.iter()
+            .map(|&c| c + trans)
+            .collect()
+    }
+}
+
+
+// Board
+// =====
+
+/// A hexagonal board to fit the pieces in.
+///
+/// The cells are stored in a parallelogram of axial coordinates,
+/// and the empty cells carve out the actual shape of the board.
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Board {
+    cells: Vec<Vec<Cell>>,
+}
+
+impl Board {
+    /// Creates a new board from a 2D list of cells, indexed by `cells[r][q]`.
+    pub fn new(cells: Vec<Vec<Cell>>) -> Board {
+        assert!(!cells.is_empty());
+        Board { cells }
+    }
+
+    /// Creates a regular hexagon board of filled cells, with `radius` cells from the center to each side.
+    ///
+    /// The center is at `(radius, radius)`, so that every coordinate is non-negative.
+    pub fn hexagon(radius: usize) -> Board {
+        let n = radius as i32;
+        let center = Hex { q: n, r: n };
+        let cells = (0..=2 * n)
+            .map(|r| (0..=2 * n)
+                .map(|q| if (Hex { q, r }).distance(center) <= n { Cell::Filled } else { Cell::Empty })
+                .collect())
+            .collect();
+        Board::new(cells)
+    }
+
+    /// Returns a 2D list of cells, indexed by `cells[r][q]`.
+    pub fn cells(&self) -> &Vec<Vec<Cell>> { &self.cells }
+
+    /// Returns the coordinates of the cells that pieces can be placed on, i.e. filled and wildcard cells.
+    pub fn positions(&self) -> impl Iterator<Item = Hex> + '_ {
+        self.cells.iter().enumerate()
+            .flat_map(|(r, row)| row.iter().enumerate()
+                .filter(|(_, &cell)| cell != Cell::Empty)
+                .map(move |(q, _)| Hex { q: q as i32, r: r as i32 }))
+    }
+
+    /// Returns whether the given piece can fit in the board
+    /// with specified orientation and translation.
+    pub fn piece_fits(
+        &self,
+        piece: &Polyhex,
+        orien: Orientation,
+        trans: Hex,
+    ) -> bool {
+        for c in piece.orient(orien).translated_cells(trans) {
+            if self.cell(c).unwrap_or(&Cell::Empty) == &Cell::Empty { return false }
+        }
+        true
+    }
+
+    fn cell(&self, Hex { q, r }: Hex) -> Option<&Cell> {
+        self.cells.get(usize::try_from(r).ok()?)?
+            .get(usize::try_from(q).ok()?)
+    }
+}
+
+
+// Problem
+// =======
+
+/// An identifier of a piece placed in a specified orientation and translation.
+/// It is used as a subset name of [`Problem`] instance.
+pub type CompoundName<N> = (N, Orientation, Hex);
+
+/// An exact cover constraint for polyhex packing problem.
+#[derive(PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(test, derive(Debug))]
+pub enum CompoundConstraint<N> {
+    Piece(N),
+    Cell(Hex),
+}
+
+/// A polyhex packing problem.
+#[cfg_attr(test, derive(Debug))]
+pub struct PolyhexPacking<N: Value> {
+    board: Board,
+    pieces: IndexMap<N, Polyhex>,
+    min: IndexMap<N, usize>,
+    max: IndexMap<N, usize>,
+}
+
+impl<N: Value> Default for PolyhexPacking<N> {
+    fn default() -> PolyhexPacking<N> {
+        PolyhexPacking {
+            board: Default::default(),
+            pieces: Default::default(),
+            min: Default::default(),
+            max: Default::default(),
+        }
+    }
+}
+
+impl<N: Value> PolyhexPacking<N> {
+    /// Creates an empty problem.
+    pub fn new() -> PolyhexPacking<N> {
+        PolyhexPacking::default()
+    }
+
+    /// Returns a reference to the board.
+    pub fn board(&self) -> &Board { &self.board }
+    /// Returns a mutable reference to the board.
+    pub fn board_mut(&mut self) -> &mut Board { &mut self.board }
+    /// Sets the board.
+    pub fn set_board(&mut self, board: Board) { self.board = board; }
+    /// Returns a reference to the pieces.
+    pub fn pieces(&self) -> &IndexMap<N, Polyhex> { &self.pieces }
+
+    /// Adds a piece to the problem.
+    ///
+    /// If the piece name already exists, it replaces the corresponding piece.
+    pub fn add_piece(&mut self, name: N, piece: Polyhex) {
+        self.pieces.insert(name.clone(), piece);
+        self.min.insert(name.clone(), 1);
+        self.max.insert(name.clone(), 1);
+    }
+
+    /// Sets a piece's multiplicity range.
+    pub fn set_piece_range(&mut self, name: N, min: usize, max: usize) {
+        if self.pieces.contains_key(&name) {
+            self.min[&name] = min;
+            self.max[&name] = max;
+        }
+    }
+
+    /// Generates an exact cover problem instance ([`Problem`]).
+    pub fn generate_problem(&self) -> Problem<CompoundName<N>, CompoundConstraint<N>> {
+        let mut prob = Problem::<CompoundName<N>, CompoundConstraint<N>>::default();
+
+        // Piece constraints
+        for name in self.pieces.keys() {
+            prob.add_constraint(CompoundConstraint::Piece(name.clone()), self.min[name], self.max[name]);
+        }
+
+        // Cell contraints
+        for c in self.board.positions() {
+            match self.board.cell(c).unwrap() {
+                Cell::Wildcard => prob.add_constraint(CompoundConstraint::Cell(c), 0, 1),
+                _ => prob.add_constraint(CompoundConstraint::Cell(c), 1, 1),
+            }
+        }
+
+        // Subsets
+        for (name, piece) in &self.pieces {
+            for o in piece.unique_orientations() {
+                let p = piece.orient(o);
+                // Each placement puts the first cell of the piece on a distinct board cell
+                for c in self.board.positions() {
+                    let t = c - p.cells[0];
+                    if !self.board.piece_fits(piece, o, t) { continue }
+
+                    let mut subset = vec![CompoundConstraint::Piece(name.clone())];
+                    subset.extend(p.translated_cells(t).into_iter().map(CompoundConstraint::Cell));
+                    prob.add_subset((name.clone(), o, t), subset);
+                }
+            }
+        }
+
+        prob
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solver;
+
+    fn h(q: i32, r: i32) -> Hex { Hex { q, r } }
+
+    #[test]
+    fn orientations_are_distinct() {
+        let images: IndexSet<_> = Orientation::all()
+            .map(|o| (o.apply(h(1, 0)), o.apply(h(0, 1))))
+            .collect();
+        assert_eq!(images.len(), 12);
+    }
+
+    #[test]
+    fn unique_orientations_can_be_found() {
+        let unit = Polyhex::new(&[h(0, 0)]).unwrap();
+        assert_eq!(unit.unique_orientations().len(), 1);
+
+        let bar = Polyhex::new(&[h(0, 0), h(1, 0), h(2, 0)]).unwrap();
+        assert_eq!(bar.unique_orientations().len(), 3);
+
+        let triangle = Polyhex::new(&[h(0, 0), h(1, 0), h(0, 1)]).unwrap();
+        assert_eq!(triangle.unique_orientations().len(), 2);
+
+        let chiral = Polyhex::new(&[h(0, 0), h(1, 0), h(2, 0), h(2, 1)]).unwrap();
+        assert_eq!(chiral.unique_orientations().len(), 12);
+
+        assert_eq!(Polyhex::new(&[]), Err(InvalidPieceError::Blank));
+        assert_eq!(Polyhex::new(&[h(1, 1), h(1, 1)]), Err(InvalidPieceError::DuplicateCell));
+    }
+
+    #[test]
+    fn hexagon_can_be_packed() {
+        let board = Board::hexagon(1);
+        assert_eq!(board.positions().count(), 7);
+
+        let mut prob = PolyhexPacking::new();
+        prob.set_board(board);
+        prob.add_piece("unit", Polyhex::new(&[h(0, 0)]).unwrap());
+        prob.set_piece_range("unit", 7, 7);
+        let solutions = Solver::new(prob.generate_problem()).solve_blocking().unwrap();
+        assert_eq!(solutions.len(), 1);
+
+        // A bar of 3 cells must pass through the center, in one of the 3 directions
+        prob.add_piece("bar", Polyhex::new(&[h(0, 0), h(1, 0), h(2, 0)]).unwrap());
+        prob.set_piece_range("unit", 4, 4);
+        let solutions = Solver::new(prob.generate_problem()).solve_blocking().unwrap();
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all(|sol| sol.len() == 5));
+    }
+}
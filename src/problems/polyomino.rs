@@ -2,8 +2,11 @@
 
 use std::error::Error;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::OnceLock;
 use indexmap::{IndexMap, IndexSet};
-use crate::problem::{Problem, Value};
+use crate::problem::{Problem, Subset, Value};
 use crate::vector::Vector2D;
 
 // Orientation
@@ -19,31 +22,117 @@ pub struct Orientation {
     pub rotation: i32, // 0..4
 }
 
+impl Orientation {
+    /// Returns all 8 orientations.
+    pub fn all() -> impl Iterator<Item = Orientation> {
+        [false, true].into_iter()
+            .flat_map(|reflection| (0..4).map(move |rotation| Orientation { reflection, rotation }))
+    }
+
+    /// Applies the orientation to a vector.
+    pub fn apply(&self, v: Vector2D) -> Vector2D {
+        let v = if self.reflection { Vector2D { x: -v.x, y: v.y } } else { v };
+        v.rotate(self.rotation)
+    }
+
+    /// Returns the orientation that applies `self` first, and then `other`.
+    pub fn compose(self, other: Orientation) -> Orientation {
+        // Reflecting after a rotation is the same as rotating in the opposite direction after reflecting.
+        let rotation = if other.reflection { -self.rotation } else { self.rotation };
+        Orientation {
+            reflection: self.reflection != other.reflection,
+            rotation: (rotation + other.rotation).rem_euclid(4),
+        }
+    }
+
+    /// Returns the orientation that reverts `self`.
+    pub fn inverse(self) -> Orientation {
+        Orientation {
+            reflection: self.reflection,
+            // A reflected orientation is its own inverse
+            rotation: if self.reflection { self.rotation.rem_euclid(4) } else { (-self.rotation).rem_euclid(4) },
+        }
+    }
+}
+
 
 // Polyomino
 // =========
 
-/// A polyomino piece, possibly with disconnected cells.
+/// A polyomino piece, possibly with disconnected cells (see [`is_connected`](Polyomino::is_connected)).
 /// 
 /// The coordinates are normalized upon creation,
 /// so it does not contain translation information.
-#[derive(PartialEq, Eq, Hash, Default)]
+/// 
+/// By default, the piece can be both rotated and reflected.
+/// Use [`with_symmetry`](Polyomino::with_symmetry) to restrict them.
+/// 
+/// The cells may also have colors (see [`with_colors`](Polyomino::with_colors)),
+/// which are rotated and reflected together with the cells.
 #[cfg_attr(test, derive(Debug))]
 pub struct Polyomino {
     cells: Vec<Vector2D>,
+    colors: Vec<Color>, // in the same order as the cells, or empty if uncolored
     size: Vector2D,
-    // TODO: add flags to configure the piece (e.g. rotation, reflection, etc.)
+    allow_rotation: bool,
+    allow_reflection: bool,
+    unique_orientations: OnceLock<Vec<Orientation>>, // computed on the first use
+}
+
+impl Default for Polyomino {
+    fn default() -> Self {
+        Polyomino {
+            cells: Vec::new(),
+            colors: Vec::new(),
+            size: Vector2D::default(),
+            allow_rotation: true,
+            allow_reflection: true,
+            unique_orientations: OnceLock::new(),
+        }
+    }
+}
+
+// The cached orientations are derived from the other fields, so they are not compared.
+impl PartialEq for Polyomino {
+    fn eq(&self, other: &Polyomino) -> bool {
+        self.cells == other.cells
+            && self.colors == other.colors
+            && self.size == other.size
+            && self.allow_rotation == other.allow_rotation
+            && self.allow_reflection == other.allow_reflection
+    }
+}
+
+impl Eq for Polyomino {}
+
+impl Hash for Polyomino {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cells.hash(state);
+        self.colors.hash(state);
+        self.size.hash(state);
+        self.allow_rotation.hash(state);
+        self.allow_reflection.hash(state);
+    }
 }
 
 /// An error returned when an invalid piece is given.
-/// 
-/// Currently, it only occurs when the given piece is blank.
-#[derive(Debug)]
-pub struct InvalidPieceError;
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InvalidPieceError {
+    /// The piece has no cells.
+    Blank,
+    /// The piece is required to be connected, but it is not.
+    Disconnected,
+    /// The same cell is given more than once.
+    DuplicateCell,
+}
 
 impl Display for InvalidPieceError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Invalid (blank) piece.")
+        match self {
+            InvalidPieceError::Blank => write!(f, "Invalid (blank) piece."),
+            InvalidPieceError::Disconnected => write!(f, "Invalid (disconnected) piece."),
+            InvalidPieceError::DuplicateCell => write!(f, "Invalid (duplicate cell) piece."),
+        }
     }
 }
 
@@ -54,9 +143,9 @@ impl Polyomino {
     /// 
     /// The coordinates are normalized upon creation,
     /// i.e., the minimums of x/y coordinates are set to 0.
-    /// It returns an error if the given list is empty.
+    /// It returns an error if the given list is empty or contains the same cell twice.
     pub fn new(cells: &[Vector2D]) -> Result<Polyomino, InvalidPieceError> {
-        if cells.len() == 0 { return Err(InvalidPieceError) }
+        if cells.is_empty() { return Err(InvalidPieceError::Blank) }
 
         let min_x = cells.iter().map(|c| { c.x }).min().unwrap();
         let max_x = cells.iter().map(|c| { c.x }).max().unwrap();
@@ -67,16 +156,52 @@ impl Polyomino {
             .map(|&c| { c - Vector2D { x: min_x, y: min_y } })
             .collect();
         normalized_cells.sort();
+        if normalized_cells.windows(2).any(|w| w[0] == w[1]) { return Err(InvalidPieceError::DuplicateCell) }
 
         Ok(Polyomino {
             cells: normalized_cells,
+            colors: Vec::new(),
             size: Vector2D {
                 x: max_x - min_x + 1,
                 y: max_y - min_y + 1,
-            }
+            },
+            allow_rotation: true,
+            allow_reflection: true,
+            unique_orientations: OnceLock::new(),
         })
     }
 
+    /// Creates a new `Polyomino` like [`new`](Polyomino::new),
+    /// but it also returns an error if the cells are not connected (see [`is_connected`](Polyomino::is_connected)).
+    pub fn new_connected(cells: &[Vector2D]) -> Result<Polyomino, InvalidPieceError> {
+        let piece = Polyomino::new(cells)?;
+        if !piece.is_connected() { return Err(InvalidPieceError::Disconnected) }
+        Ok(piece)
+    }
+
+    /// Sets whether the piece can be rotated and reflected.
+    /// 
+    /// For example, a one-sided piece which cannot be flipped over
+    /// is expressed by `with_symmetry(true, false)`,
+    /// and a fixed piece is expressed by `with_symmetry(false, false)`.
+    pub fn with_symmetry(mut self, allow_rotation: bool, allow_reflection: bool) -> Polyomino {
+        self.allow_rotation = allow_rotation;
+        self.allow_reflection = allow_reflection;
+        self.unique_orientations = OnceLock::new();
+        self
+    }
+
+    /// Colors the cells, where `colors[i]` is the color of `cells()[i]`.
+    /// 
+    /// Adjacent cells of different colored pieces must have the same color
+    /// in a packing (see [`PolyominoPacking::generate_problem`]).
+    pub fn with_colors(mut self, colors: Vec<Color>) -> Polyomino {
+        assert_eq!(colors.len(), self.cells.len(), "Each cell must have a color");
+        self.colors = colors;
+        self.unique_orientations = OnceLock::new();
+        self
+    }
+
     /// Convenience function to create a new `Polyomino` from a bytes array.
     /// 
     /// It uses the inverted y-axis coordinate system.
@@ -86,9 +211,9 @@ impl Polyomino {
     pub fn from_bytes_array(array: &[&[u8]]) -> Result<Polyomino, InvalidPieceError> {
         let mut cells = Vec::new();
 
-        for y in 0..array.len() {
-            for x in 0..array[y].len() {
-                if array[y][x] == b'#' {
+        for (y, row) in array.iter().enumerate() {
+            for (x, &byte) in row.iter().enumerate() {
+                if byte == b'#' {
                     cells.push(Vector2D { x: x as i32, y: y as i32 });
                 }
             }
@@ -101,6 +226,16 @@ impl Polyomino {
     pub fn cells(&self) -> &Vec<Vector2D> { &self.cells }
     /// Returns the size of the bounding box.
     pub fn size(&self) -> Vector2D { self.size }
+    /// Returns the colors of the cells in the same order, if the piece is colored.
+    pub fn colors(&self) -> Option<&[Color]> {
+        if self.colors.is_empty() { None } else { Some(&self.colors) }
+    }
+    /// Returns the number of cells in the piece.
+    pub fn area(&self) -> usize { self.cells.len() }
+    /// Returns whether the piece can be rotated.
+    pub fn allows_rotation(&self) -> bool { self.allow_rotation }
+    /// Returns whether the piece can be reflected.
+    pub fn allows_reflection(&self) -> bool { self.allow_reflection }
     
     /// Orients the piece according to the given orientation.
     /// Reflection is applied first, then rotation.
@@ -113,29 +248,68 @@ impl Polyomino {
 
     /// Reflects the piece in the y axis.
     pub fn reflect(&self) -> Polyomino {
-        let reflected: Vec<_> = self.cells.iter()
-            .map(|&Vector2D { x, y }| { Vector2D { x: -x, y } })
-            .collect();
-
-        Polyomino::new(&reflected).unwrap()
+        self.map_cells(|Vector2D { x, y }| { Vector2D { x: -x, y } })
     }
 
     /// Rotates the piece as specified amount.
     pub fn rotate(&self, rotation: i32) -> Polyomino {
-        let rotated: Vec<_> = self.cells.iter()
-            .map(|c| { c.rotate(rotation) })
-            .collect();
+        self.map_cells(|c| { c.rotate(rotation) })
+    }
+
+    /// Moves each cell by `f` and normalizes the piece again.
+    /// The colors move together with the cells.
+    fn map_cells(&self, f: impl Fn(Vector2D) -> Vector2D) -> Polyomino {
+        let mapped: Vec<_> = self.cells.iter().map(|&c| f(c)).collect();
+        let mut piece = Polyomino::new(&mapped).unwrap()
+            .with_symmetry(self.allow_rotation, self.allow_reflection);
 
-        Polyomino::new(&rotated).unwrap()
+        if !self.colors.is_empty() {
+            // The normalized cells are sorted, so find where each cell has moved
+            let min = Vector2D {
+                x: mapped.iter().map(|c| c.x).min().unwrap(),
+                y: mapped.iter().map(|c| c.y).min().unwrap(),
+            };
+            let mut colors = vec![0; mapped.len()];
+            for (&c, &color) in mapped.iter().zip(&self.colors) {
+                colors[piece.cells.binary_search(&(c - min)).unwrap()] = color;
+            }
+            piece.colors = colors;
+        }
+        piece
     }
 
     /// Returns possible orientations of the piece without duplication.
-    pub fn unique_orientations(&self) -> Vec<Orientation> {
+    /// 
+    /// Only the orientations allowed by the symmetry flags are returned.
+    /// They are computed on the first call, and cached afterwards.
+    pub fn unique_orientations(&self) -> &[Orientation] {
+        self.unique_orientations.get_or_init(|| self.compute_unique_orientations())
+    }
+
+    /// Returns possible orientations of the piece without duplication,
+    /// when it is further restricted by the given flags.
+    fn restricted_orientations(&self, allow_rotation: bool, allow_reflection: bool) -> Vec<Orientation> {
+        let allow_rotation = self.allow_rotation && allow_rotation;
+        let allow_reflection = self.allow_reflection && allow_reflection;
+        if (allow_rotation, allow_reflection) == (self.allow_rotation, self.allow_reflection) {
+            self.unique_orientations().to_vec()
+        } else {
+            self.orientations_with(allow_rotation, allow_reflection)
+        }
+    }
+
+    fn compute_unique_orientations(&self) -> Vec<Orientation> {
+        self.orientations_with(self.allow_rotation, self.allow_reflection)
+    }
+
+    fn orientations_with(&self, allow_rotation: bool, allow_reflection: bool) -> Vec<Orientation> {
         let mut pieces = IndexSet::new();
         let mut res = Vec::new();
+        let reflections: &[bool] = if allow_reflection { &[false, true] } else { &[false] };
+        let rotations = if allow_rotation { 4 } else { 1 };
         
-        for reflection in [false, true] {
-            for rotation in 0..4 {
+        for &reflection in reflections {
+            for rotation in 0..rotations {
                 let o = Orientation { reflection, rotation };
                 let piece = self.orient(o);
                 if !pieces.contains(&piece) {
@@ -153,6 +327,40 @@ impl Polyomino {
             .map(|&c| { c + trans })
             .collect()
     }
+
+    /// Returns whether every cell can be reached from the others
+    /// through the cells sharing an edge.
+    pub fn is_connected(&self) -> bool {
+        if self.cells.is_empty() { return true }
+
+        let mut visited = vec![false; self.cells.len()];
+        let mut stack = vec![0];
+        visited[0] = true;
+
+        while let Some(i) = stack.pop() {
+            for r in 0..4 {
+                let neighbor = self.cells[i] + Vector2D { x: 1, y: 0 }.rotate(r);
+                if let Ok(j) = self.cells.binary_search(&neighbor) {
+                    if !visited[j] {
+                        visited[j] = true;
+                        stack.push(j);
+                    }
+                }
+            }
+        }
+        visited.into_iter().all(|v| v)
+    }
+}
+
+impl FromStr for Polyomino {
+    type Err = InvalidPieceError;
+
+    /// Parses a piece from a multiline string, in the same way as
+    /// [`from_bytes_array`](Polyomino::from_bytes_array) does for each line.
+    fn from_str(s: &str) -> Result<Polyomino, InvalidPieceError> {
+        let lines: Vec<_> = s.lines().map(|line| line.as_bytes()).collect();
+        Polyomino::from_bytes_array(&lines)
+    }
 }
 
 
@@ -160,27 +368,41 @@ impl Polyomino {
 // ========
 
 /// A board cell.
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
 #[cfg_attr(test, derive(Debug))]
 pub enum Cell {
     /// A cell that has to be filled by a piece.
     Filled,
     /// A cell that has to be left empty.
+    #[default]
     Empty,
     /// A cell that can be either filled or left empty.
     Wildcard,
 }
 
-impl Default for Cell {
-    fn default() -> Self { Cell::Empty }
+/// An error returned when an invalid board is given.
+/// 
+/// Currently, it only occurs when the given board has no cells.
+#[derive(Debug)]
+pub struct InvalidBoardError;
+
+impl Display for InvalidBoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid (blank) board.")
+    }
 }
 
+impl Error for InvalidBoardError {}
+
 /// A board to fit the pieces in.
 #[derive(Default)]
 #[cfg_attr(test, derive(Debug))]
 pub struct Board {
     cells: Vec<Vec<Cell>>,
     size: Vector2D,
+    wrap_x: bool,
+    wrap_y: bool,
+    regions: Vec<(Vec<Vector2D>, usize, usize)>, // cells and multiplicity range of each region
 }
 
 impl Board {
@@ -189,7 +411,7 @@ impl Board {
     /// It uses the inverted y-axis coordinate system.
     pub fn new(cells: Vec<Vec<Cell>>) -> Board {
         // TODO: validate parameter
-        assert!(cells.len() > 0);
+        assert!(!cells.is_empty());
         
         Board {
             size: Vector2D {
@@ -197,6 +419,9 @@ impl Board {
                 x: cells[0].len() as i32,
             },
             cells,
+            wrap_x: false,
+            wrap_y: false,
+            regions: Vec::new(),
         }
     }
 
@@ -226,6 +451,49 @@ impl Board {
     pub fn cells(&self) -> &Vec<Vec<Cell>> { &self.cells }
     /// Returns the size of the board.
     pub fn size(&self) -> Vector2D { self.size }
+    /// Returns whether the board wraps around in the x and y directions.
+    pub fn wraps(&self) -> (bool, bool) { (self.wrap_x, self.wrap_y) }
+
+    /// Makes the board wrap around in the x and/or y directions, e.g. both for a torus.
+    /// 
+    /// A piece crossing an edge of a wrapped direction continues from the opposite edge,
+    /// so the translated cells of a placed piece may be out of the board (see [`wrap`](Board::wrap)).
+    /// A piece still cannot overlap itself after wrapping around.
+    pub fn set_wrap(&mut self, x: bool, y: bool) {
+        self.wrap_x = x;
+        self.wrap_y = y;
+    }
+
+    /// Adds a region of cells that has to be overlapped by at least `min` and at most `max` placed pieces,
+    /// and returns its id, which counts the regions added before.
+    /// 
+    /// A placed piece counts once if it covers any cell of the region
+    /// (see [`CompoundConstraint::Region`]). The regions may overlap each other.
+    /// It panics if a cell is out of the board, or if `min` is greater than `max`.
+    pub fn add_region(&mut self, cells: Vec<Vector2D>, min: usize, max: usize) -> usize {
+        assert!(cells.iter().all(|&c| !self.out_of_bounds(c)), "Region cells must be in the board");
+        assert!(min <= max, "Region has a minimum multiplicity {} greater than its maximum {}", min, max);
+        self.regions.push((cells, min, max));
+        self.regions.len() - 1
+    }
+
+    /// Returns the board cell that `c` refers to,
+    /// i.e. the coordinates are taken modulo the size in the wrapped directions.
+    pub fn wrap(&self, c: Vector2D) -> Vector2D {
+        Vector2D {
+            x: if self.wrap_x { c.x.rem_euclid(self.size.x) } else { c.x },
+            y: if self.wrap_y { c.y.rem_euclid(self.size.y) } else { c.y },
+        }
+    }
+
+    /// Returns the number of cells that pieces can be placed on, i.e. filled and wildcard cells.
+    pub fn empty_count(&self) -> usize {
+        self.count(Cell::Filled) + self.count(Cell::Wildcard)
+    }
+
+    fn count(&self, cell: Cell) -> usize {
+        self.cells.iter().flatten().filter(|&&c| c == cell).count()
+    }
 
     /// Returns whether the given piece can fit in the board
     /// with specified orientation and translation.
@@ -235,17 +503,97 @@ impl Board {
         orien: Orientation,
         trans: Vector2D,
     ) -> bool {
-        for c in piece.orient(orien).translated_cells(trans) {
+        let mut cells: Vec<_> = piece.orient(orien).translated_cells(trans).into_iter()
+            .map(|c| self.wrap(c))
+            .collect();
+
+        for &c in &cells {
             let Vector2D { x, y } = c;
             if self.out_of_bounds(c) { return false }
             if self.cells[y as usize][x as usize] == Cell::Empty { return false }
         }
-        true
+
+        // The piece may overlap itself after wrapping around
+        cells.sort();
+        cells.windows(2).all(|w| w[0] != w[1])
+    }
+
+    /// Returns the orientations that map the board onto itself,
+    /// including the identity orientation.
+    /// 
+    /// Wildcard and empty cells have to be mapped onto the same kind of cells,
+    /// and each region onto a region with the same multiplicity range.
+    pub fn symmetries(&self) -> Vec<Orientation> {
+        let sorted = |cells: &[Vector2D]| {
+            let mut cells = cells.to_vec();
+            cells.sort();
+            cells
+        };
+        let regions: Vec<_> = self.regions.iter()
+            .map(|(cells, min, max)| (sorted(cells), *min, *max))
+            .collect();
+
+        Orientation::all()
+            .filter(|&o| {
+                (0..self.size.y).all(|y| (0..self.size.x).all(|x| {
+                    let c = self.transform(o, Vector2D { x, y });
+                    !self.out_of_bounds(c)
+                        && self.cells[c.y as usize][c.x as usize] == self.cells[y as usize][x as usize]
+                }))
+            })
+            .filter(|&o| {
+                regions.iter().all(|(cells, min, max)| {
+                    let image: Vec<_> = cells.iter().map(|&c| self.transform(o, c)).collect();
+                    regions.contains(&(sorted(&image), *min, *max))
+                })
+            })
+            .collect()
+    }
+
+    /// Orients cell `c` together with the whole board,
+    /// and then translates it back so that the board's bounding box starts from the origin.
+    fn transform(&self, o: Orientation, c: Vector2D) -> Vector2D {
+        let Vector2D { x: w, y: h } = self.size;
+        let corners = [(0, 0), (w - 1, 0), (0, h - 1), (w - 1, h - 1)]
+            .map(|(x, y)| o.apply(Vector2D { x, y }));
+        let min = Vector2D {
+            x: corners.iter().map(|c| c.x).min().unwrap(),
+            y: corners.iter().map(|c| c.y).min().unwrap(),
+        };
+        o.apply(c) - min
     }
 
     fn out_of_bounds(&self, Vector2D { x, y }: Vector2D) -> bool {
         x < 0 || x >= self.size.x || y < 0 || y >= self.size.y
     }
+
+    /// Returns whether a piece can be placed on cell `c` after wrapping.
+    fn has_cell(&self, c: Vector2D) -> bool {
+        let c = self.wrap(c);
+        !self.out_of_bounds(c) && self.cells[c.y as usize][c.x as usize] != Cell::Empty
+    }
+}
+
+impl FromStr for Board {
+    type Err = InvalidBoardError;
+
+    /// Parses a board from a multiline string, in the same way as
+    /// [`from_bytes_array`](Board::from_bytes_array) does for each line.
+    /// 
+    /// Lines shorter than the longest one are padded with empty cells,
+    /// and a trailing newline is ignored.
+    fn from_str(s: &str) -> Result<Board, InvalidBoardError> {
+        let lines: Vec<_> = s.lines().map(|line| line.as_bytes()).collect();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        if width == 0 { return Err(InvalidBoardError) }
+
+        let mut board = Board::from_bytes_array(&lines);
+        for row in &mut board.cells {
+            row.resize(width, Cell::Empty);
+        }
+        board.size.x = width as i32;
+        Ok(board)
+    }
 }
 
 
@@ -256,30 +604,95 @@ impl Board {
 /// It is used as a subset name of [`Problem`] instance.
 pub type CompoundName<N> = (N, Orientation, Vector2D);
 
+/// A color of a piece cell (see [`Polyomino::with_colors`]).
+pub type Color = usize;
+
+/// The cells and the colors of a piece in each of its orientations.
+type OrientedShapes = Vec<(Vec<Vector2D>, Vec<Color>)>;
+
+/// A placed piece with its subset and the colors of its edges.
+type Placement<N> = (CompoundName<N>, Subset<CompoundConstraint<N>>, Vec<(CompoundConstraint<N>, Color)>);
+
 /// An exact cover constraint for polyomino packing problem.
 #[derive(PartialEq, Eq, Clone, Hash)]
 #[cfg_attr(test, derive(Debug))]
 pub enum CompoundConstraint<N> {
     Piece(N),
     Cell(Vector2D),
+    /// The edge between a cell and its neighbor in the direction `(1, 0)` or `(0, 1)`.
+    /// It is a secondary constraint colored by the colored pieces on its both sides.
+    /// 
+    /// The colors are on the edges rather than the cells,
+    /// because each cell is covered by a single piece,
+    /// while an edge is shared by the two adjacent pieces that have to match.
+    ColoredEdge(Vector2D, Vector2D),
+    /// The region with the given id (see [`Board::add_region`]),
+    /// covered once by each placed piece overlapping it.
+    Region(usize),
 }
 
 /// A polyomino packing problem.
-#[derive(Default)]
+/// 
+/// # Example
+/// 
+/// ```
+/// use exact_cover::problems::polyomino::{Board, Polyomino, PolyominoPacking};
+/// use exact_cover::Solver;
+/// 
+/// let mut prob = PolyominoPacking::new();
+/// prob.set_board(Board::from_bytes_array(&[b"###", b"###"]));
+/// prob.add_piece("L", Polyomino::from_bytes_array(&[b"##", b"#."]).unwrap());
+/// prob.set_piece_range("L", 2, 2);
+/// 
+/// let solutions = Solver::new(prob.generate_problem()).solve_blocking().unwrap();
+/// assert_eq!(solutions.len(), 2);
+/// ```
 #[cfg_attr(test, derive(Debug))]
 pub struct PolyominoPacking<N: Value> {
     board: Board,
     pieces: IndexMap<N, Polyomino>,
     min: IndexMap<N, usize>,
     max: IndexMap<N, usize>,
+    allow_rotation: bool,
+    allow_reflection: bool,
+}
+
+impl<N: Value> Default for PolyominoPacking<N> {
+    fn default() -> PolyominoPacking<N> {
+        PolyominoPacking {
+            board: Default::default(),
+            pieces: Default::default(),
+            min: Default::default(),
+            max: Default::default(),
+            allow_rotation: true,
+            allow_reflection: true,
+        }
+    }
 }
 
 impl<N: Value> PolyominoPacking<N> {
+    /// Creates an empty problem.
+    pub fn new() -> PolyominoPacking<N> {
+        PolyominoPacking::default()
+    }
+
+    /// Creates a problem without pieces, whose board is a `width` x `height` rectangle of filled cells.
+    /// 
+    /// It panics if `width` or `height` is 0.
+    pub fn rectangle(width: usize, height: usize) -> PolyominoPacking<N> {
+        assert!(width > 0 && height > 0, "The board must not be empty, but it is {} x {}", width, height);
+        let mut prob = PolyominoPacking::new();
+        prob.set_board(Board::new(vec![vec![Cell::Filled; width]; height]));
+        prob
+    }
+
     // TODO: hide IndexMap/IndexSet from API
     /// Returns a reference to the board.
     pub fn board(&self) -> &Board { &self.board }
     /// Returns a mutable reference to the board.
     pub fn board_mut(&mut self) -> &mut Board { &mut self.board }
+    /// Sets the board.
+    pub fn set_board(&mut self, board: Board) { self.board = board; }
     /// Returns a reference to the pieces.
     pub fn pieces(&self) -> &IndexMap<N, Polyomino> { &self.pieces }
 
@@ -292,7 +705,30 @@ impl<N: Value> PolyominoPacking<N> {
         self.max.insert(name.clone(), 1);
     }
 
-    /// Sets a piece's multiplicity range.
+    /// Adds `count` identical copies of a piece to the problem, which are used exactly `count` times.
+    /// 
+    /// Unlike adding the copies with distinct names, the copies are indistinguishable,
+    /// so the solutions differing only by their permutations are not counted separately.
+    /// If the piece name already exists, it replaces the corresponding piece.
+    pub fn add_pieces(&mut self, name: N, piece: Polyomino, count: usize) {
+        self.add_piece(name.clone(), piece);
+        self.set_piece_range(name, count, count);
+    }
+
+    /// Colors the cells of a piece (see [`Polyomino::with_colors`]).
+    /// 
+    /// It does nothing if the piece does not exist.
+    pub fn set_piece_colors(&mut self, name: N, colors: Vec<Color>) {
+        if let Some(piece) = self.pieces.get_mut(&name) {
+            *piece = std::mem::take(piece).with_colors(colors);
+        }
+    }
+
+    /// Sets a piece's multiplicity range,
+    /// i.e., the piece is used at least `min` times and at most `max` times.
+    /// 
+    /// Each piece is used exactly once by default.
+    /// It does nothing if the piece does not exist.
     pub fn set_piece_range(&mut self, name: N, min: usize, max: usize) {
         if self.pieces.contains_key(&name) {
             self.min[&name] = min;
@@ -300,8 +736,104 @@ impl<N: Value> PolyominoPacking<N> {
         }
     }
 
+    /// Merges the pieces that have the same shapes in their allowed orientations
+    /// into the first of them, whose multiplicity range becomes the sum of theirs.
+    /// 
+    /// Like the copies added by [`add_pieces`](PolyominoPacking::add_pieces),
+    /// the merged pieces are indistinguishable, so it changes the solutions:
+    /// they only name the first piece, and the solutions differing only by
+    /// the permutations of the merged pieces are found once.
+    /// It returns the names of the merged pieces, each with the name it has been merged into.
+    pub fn dedup_pieces(&mut self) -> Vec<(N, N)> {
+        let mut shapes: Vec<(OrientedShapes, &N)> = Vec::new(); // of each distinct piece
+        let mut merged = Vec::new();
+
+        for (name, piece) in &self.pieces {
+            let piece_shapes = self.oriented_shapes(piece);
+            match shapes.iter().find(|(s, _)| *s == piece_shapes) {
+                Some(&(_, first)) => merged.push((name.clone(), first.clone())),
+                None => shapes.push((piece_shapes, name)),
+            }
+        }
+
+        for (name, first) in &merged {
+            self.pieces.shift_remove(name);
+            let min = self.min.shift_remove(name).unwrap();
+            let max = self.max.shift_remove(name).unwrap();
+            self.min[first] = self.min[first].saturating_add(min);
+            self.max[first] = self.max[first].saturating_add(max);
+        }
+        merged
+    }
+
+    /// Returns the cells and the colors of the piece in each allowed orientation, in a canonical order.
+    fn oriented_shapes(&self, piece: &Polyomino) -> OrientedShapes {
+        let mut shapes: Vec<_> = piece.restricted_orientations(self.allow_rotation, self.allow_reflection)
+            .into_iter()
+            .map(|o| {
+                let p = piece.orient(o);
+                (p.cells, p.colors)
+            })
+            .collect();
+        shapes.sort();
+        shapes
+    }
+
+    /// Sets whether the pieces can be rotated, in addition to their own flags
+    /// (see [`Polyomino::with_symmetry`]).
+    /// 
+    /// It is allowed by default, so only the flags of each piece are considered.
+    pub fn set_allow_rotation(&mut self, allow_rotation: bool) {
+        self.allow_rotation = allow_rotation;
+    }
+
+    /// Sets whether the pieces can be reflected, in addition to their own flags
+    /// (see [`Polyomino::with_symmetry`]).
+    /// 
+    /// For example, `set_allow_reflection(false)` makes every piece one-sided.
+    /// It is allowed by default, so only the flags of each piece are considered.
+    pub fn set_allow_reflection(&mut self, allow_reflection: bool) {
+        self.allow_reflection = allow_reflection;
+    }
+
+    /// Returns whether the total area of the pieces can fit the board, regardless of their shapes.
+    /// 
+    /// It returns `false` if the pieces used `min` times already cover more cells
+    /// than the board has (see [`Board::empty_count`]),
+    /// or if the pieces used `max` times cannot cover all the filled cells.
+    /// It is much cheaper than [`generate_problem`](PolyominoPacking::generate_problem),
+    /// but the problem may still have no solution even if it returns `true`.
+    pub fn is_area_feasible(&self) -> bool {
+        let total_area = |multiplicity: &IndexMap<N, usize>| {
+            self.pieces.iter()
+                .map(|(name, piece)| multiplicity[name].saturating_mul(piece.area()))
+                .fold(0, usize::saturating_add)
+        };
+        total_area(&self.min) <= self.board.empty_count()
+            && total_area(&self.max) >= self.board.count(Cell::Filled)
+    }
+
     /// Generates an exact cover problem instance ([`Problem`]).
+    /// 
+    /// If any piece is colored, the edges between adjacent board cells become
+    /// secondary constraints ([`CompoundConstraint::ColoredEdge`]), so that a cell of a colored piece
+    /// and an adjacent cell of another colored piece must have the same color.
+    /// Uncolored pieces can be adjacent to any piece.
     pub fn generate_problem(&self) -> Problem<CompoundName<N>, CompoundConstraint<N>> {
+        let mut prob = self.generate_constraints();
+
+        // Subsets
+        for (name, piece) in &self.pieces {
+            for (compound_name, subset, colors) in self.generate_placements(name, piece) {
+                prob.insert_colored_subset(compound_name, subset, colors);
+            }
+        }
+
+        prob
+    }
+
+    /// Generates a problem with the constraints only.
+    fn generate_constraints(&self) -> Problem<CompoundName<N>, CompoundConstraint<N>> {
         let mut prob = Problem::<CompoundName<N>, CompoundConstraint<N>>::default();
 
         // Piece constraints
@@ -324,19 +856,21 @@ impl<N: Value> PolyominoPacking<N> {
             }
         }
 
-        // Subsets
-        for (name, piece) in &self.pieces {
-            for o in piece.unique_orientations() {
-                let p = piece.orient(o);
-                for y in 0..=(self.board.size.y - p.size.y) {
-                    for x in 0..=(self.board.size.x - p.size.x) {
-                        let t = Vector2D { x, y };
-                        if !self.board.piece_fits(&piece, o, t) { continue }
-                        
-                        let compound_name = (name.clone(), o, t);
-                        let subset = Self::generate_subset(name.clone(), &p, t);
-                        
-                        prob.add_subset(compound_name, subset);
+        // Region constraints
+        for (id, &(_, min, max)) in self.board.regions.iter().enumerate() {
+            prob.add_constraint(CompoundConstraint::Region(id), min, max);
+        }
+
+        // Edge constraints
+        if self.pieces.values().any(|piece| piece.colors().is_some()) {
+            for y in 0..self.board.size.y {
+                for x in 0..self.board.size.x {
+                    let c = Vector2D { x, y };
+                    if !self.board.has_cell(c) { continue }
+                    for d in [Vector2D { x: 1, y: 0 }, Vector2D { x: 0, y: 1 }] {
+                        if self.board.has_cell(c + d) && self.board.wrap(c + d) != c {
+                            prob.add_secondary_constraint(CompoundConstraint::ColoredEdge(c, d));
+                        }
                     }
                 }
             }
@@ -345,37 +879,388 @@ impl<N: Value> PolyominoPacking<N> {
         prob
     }
 
+    /// Returns the subsets of every possible placement of a piece, with their colored edges.
+    fn generate_placements(&self, name: &N, piece: &Polyomino) -> Vec<Placement<N>> {
+        let mut res = Vec::new();
+        // Different translations may cover the same cells with the same colors after wrapping around
+        let mut placements = IndexSet::new();
+
+        for o in piece.restricted_orientations(self.allow_rotation, self.allow_reflection) {
+            let p = piece.orient(o);
+            // A piece can start from any cell in a wrapped direction
+            let max_y = if self.board.wrap_y { self.board.size.y - 1 } else { self.board.size.y - p.size.y };
+            let max_x = if self.board.wrap_x { self.board.size.x - 1 } else { self.board.size.x - p.size.x };
+            for y in 0..=max_y {
+                for x in 0..=max_x {
+                    let t = Vector2D { x, y };
+                    if !self.board.piece_fits(piece, o, t) { continue }
+                    
+                    if self.board.wraps() != (false, false) {
+                        let mut cells: Vec<_> = p.translated_cells(t).into_iter()
+                            .enumerate()
+                            .map(|(i, c)| (self.board.wrap(c), p.colors().map(|colors| colors[i])))
+                            .collect();
+                        cells.sort();
+                        if !placements.insert(cells) { continue }
+                    }
+
+                    let compound_name = (name.clone(), o, t);
+                    let subset = self.generate_subset(name.clone(), &p, t);
+                    
+                    res.push((compound_name, subset, self.edge_colors(&p, t)));
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Decodes a solution into the board cells covered by each placed piece, in the order of the solution.
+    /// 
+    /// The cells of each piece are wrapped around the board if it wraps (see [`Board::wrap`]).
+    pub fn solution_cells(&self, sol: &[CompoundName<N>]) -> Vec<(N, Vec<Vector2D>)> {
+        sol.iter()
+            .map(|(name, o, t)| {
+                let cells = self.pieces[name].orient(*o).translated_cells(*t).into_iter()
+                    .map(|c| self.board.wrap(c))
+                    .collect();
+                (name.clone(), cells)
+            })
+            .collect()
+    }
+
+    /// Removes the solutions that are symmetric to the previous ones
+    /// under the symmetries of the board (see [`Board::symmetries`]).
+    /// 
+    /// Each solution is canonicalized by transforming its placed cells
+    /// with every symmetry and taking the lexicographically smallest image.
+    /// The first solution of each symmetry class is kept as its representative.
+    pub fn dedup_symmetric_solutions(
+        &self,
+        solutions: Vec<Vec<CompoundName<N>>>,
+    ) -> Vec<Vec<CompoundName<N>>> {
+        let symmetries = self.board.symmetries();
+        let mut canonical_forms = IndexSet::new();
+
+        solutions.into_iter()
+            .filter(|sol| canonical_forms.insert(self.canonical_form(sol, &symmetries)))
+            .collect()
+    }
+
+    /// Draws a solution as an SVG image, where each cell is a `cell_size` x `cell_size` square.
+    /// 
+    /// Each placed piece is a group of rectangles filled with its own color,
+    /// which is assigned in the order the pieces were added.
+    /// The uncovered cells are drawn in gray, the empty cells are left transparent,
+    /// and the board is outlined by a rectangle drawn on top.
+    pub fn solution_to_svg(&self, sol: &[CompoundName<N>], cell_size: usize) -> String {
+        let Vector2D { x: width, y: height } = self.board.size();
+        let (width, height) = (width as usize * cell_size, height as usize * cell_size);
+        let rect = |c: Vector2D| format!(
+            "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>\n",
+            c.x as usize * cell_size, c.y as usize * cell_size, cell_size, cell_size,
+        );
+
+        let mut covered = IndexSet::new();
+        let mut groups = String::new();
+        for (name, cells) in self.solution_cells(sol) {
+            let index = self.pieces.get_index_of(&name).unwrap();
+            // Consecutive pieces are a golden angle apart in hue
+            groups.push_str(&format!("  <g fill=\"hsl({}, 70%, 60%)\">\n", index * 137 % 360));
+            for c in cells {
+                covered.insert(c);
+                groups.push_str(&rect(c));
+            }
+            groups.push_str("  </g>\n");
+        }
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+            width, height,
+        );
+        svg.push_str("  <g fill=\"lightgray\">\n");
+        for (y, row) in self.board.cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let c = Vector2D { x: x as i32, y: y as i32 };
+                if *cell != Cell::Empty && !covered.contains(&c) {
+                    svg.push_str(&rect(c));
+                }
+            }
+        }
+        svg.push_str("  </g>\n");
+        svg.push_str(&groups);
+        svg.push_str(&format!(
+            "  <rect width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\"/>\n",
+            width, height,
+        ));
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Returns the smallest image of the placements in a solution, where each placement is
+    /// represented by the piece index and its sorted cells.
+    fn canonical_form(&self, sol: &[CompoundName<N>], symmetries: &[Orientation]) -> Vec<(usize, Vec<Vector2D>)> {
+        symmetries.iter()
+            .map(|&sym| {
+                let mut placements: Vec<_> = self.solution_cells(sol).into_iter()
+                    .map(|(name, cells)| {
+                        let mut cells: Vec<_> = cells.into_iter()
+                            .map(|c| self.board.transform(sym, c))
+                            .collect();
+                        cells.sort();
+                        (self.pieces.get_index_of(&name).unwrap(), cells)
+                    })
+                    .collect();
+                placements.sort();
+                placements
+            })
+            .min()
+            .unwrap() // the identity is always a symmetry
+    }
+
     fn generate_subset(
+        &self,
         name: N,
         oriented_piece: &Polyomino,
         trans: Vector2D,
-    ) -> Vec<CompoundConstraint<N>> {
-        let mut subset = Vec::new();
+    ) -> Subset<CompoundConstraint<N>> {
+        let cells: Vec<_> = oriented_piece.translated_cells(trans).into_iter()
+            .map(|c| self.board.wrap(c))
+            .collect();
+
+        let mut subset = Subset::new();
         subset.push(CompoundConstraint::Piece(name));
+        subset.extend(cells.iter().map(|&c| CompoundConstraint::Cell(c)));
         subset.extend(
-            oriented_piece.translated_cells(trans).iter()
-                .map(|&c| { CompoundConstraint::Cell(c) })
+            self.board.regions.iter().enumerate()
+                .filter(|(_, (region, _, _))| region.iter().any(|c| cells.contains(c)))
+                .map(|(id, _)| CompoundConstraint::Region(id))
         );
         subset
     }
+
+    /// Returns the colors of the edges between the placed piece and the other cells of the board.
+    fn edge_colors(
+        &self,
+        oriented_piece: &Polyomino,
+        trans: Vector2D,
+    ) -> Vec<(CompoundConstraint<N>, Color)> {
+        let Some(colors) = oriented_piece.colors() else { return Vec::new() };
+        let cells: Vec<_> = oriented_piece.translated_cells(trans).into_iter()
+            .map(|c| self.board.wrap(c))
+            .collect();
+
+        let mut edges = Vec::new();
+        for (&c, &color) in cells.iter().zip(colors) {
+            for r in 0..4 {
+                let d = Vector2D { x: 1, y: 0 }.rotate(r);
+                let neighbor = self.board.wrap(c + d);
+                if cells.contains(&neighbor) || !self.board.has_cell(neighbor) { continue }
+
+                // Each edge is named after its cell in the negative direction
+                let edge = if r < 2 {
+                    CompoundConstraint::ColoredEdge(c, d)
+                } else {
+                    CompoundConstraint::ColoredEdge(neighbor, -d)
+                };
+                edges.push((edge, color));
+            }
+        }
+        edges
+    }
+}
+
+
+impl<N: Value + Display> PolyominoPacking<N> {
+    /// Draws a solution on the board, labeling each cell with the first character of its piece name.
+    /// 
+    /// The uncovered cells are drawn as `.`, and the empty cells as spaces.
+    /// The cells are separated by spaces, and each row ends with a newline.
+    pub fn render_solution(&self, sol: &[CompoundName<N>]) -> String {
+        self.render_cells(sol, |_, label| label.to_string())
+    }
+
+    /// Draws a solution like [`render_solution`](PolyominoPacking::render_solution),
+    /// but also paints each piece in its own background color with ANSI escape codes.
+    /// 
+    /// The colors are assigned in the order the pieces were added,
+    /// and are reused after every [`ANSI_COLORS`](PolyominoPacking::ANSI_COLORS).
+    #[cfg(feature = "ansi")]
+    pub fn render_solution_ansi(&self, sol: &[CompoundName<N>]) -> String {
+        self.render_cells(sol, |index, label| {
+            let color = Self::ANSI_COLORS[index % Self::ANSI_COLORS.len()];
+            format!("\x1b[30;{}m{}\x1b[0m", color, label)
+        })
+    }
+
+    /// Draws the board with the cells of each piece drawn by `draw`,
+    /// given the index of the piece and its label.
+    fn render_cells(&self, sol: &[CompoundName<N>], draw: impl Fn(usize, char) -> String) -> String {
+        let mut buff: Vec<Vec<String>> = self.board.cells.iter()
+            .map(|row| row.iter().map(|c| if *c == Cell::Empty { " " } else { "." }.to_string()).collect())
+            .collect();
+
+        for (name, cells) in self.solution_cells(sol) {
+            let index = self.pieces.get_index_of(&name).unwrap();
+            let label = name.to_string().chars().next().unwrap_or('?');
+            for Vector2D { x, y } in cells {
+                buff[y as usize][x as usize] = draw(index, label);
+            }
+        }
+
+        buff.iter()
+            .map(|row| {
+                let mut line = row.join(" ");
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<N: Value + Send + Sync> PolyominoPacking<N> {
+    /// Generates the same problem as [`generate_problem`](PolyominoPacking::generate_problem),
+    /// but enumerates the placements of the pieces in parallel with `rayon`.
+    /// 
+    /// The subsets are still added in the same order, so the solutions are found in the same order.
+    pub fn par_generate_problem(&self) -> Problem<CompoundName<N>, CompoundConstraint<N>> {
+        use rayon::prelude::*;
+
+        let mut prob = self.generate_constraints();
+        let placements: Vec<_> = self.pieces.par_iter()
+            .map(|(name, piece)| self.generate_placements(name, piece))
+            .collect();
+
+        for (compound_name, subset, colors) in placements.into_iter().flatten() {
+            prob.insert_colored_subset(compound_name, subset, colors);
+        }
+        prob
+    }
 }
 
+#[cfg(feature = "ansi")]
+impl<N: Value> PolyominoPacking<N> {
+    /// The ANSI background color codes used by
+    /// [`render_solution_ansi`](PolyominoPacking::render_solution_ansi), in order.
+    pub const ANSI_COLORS: [u8; 12] = [41, 42, 43, 44, 45, 46, 101, 102, 103, 104, 105, 106];
+}
+
+impl PolyominoPacking<&'static str> {
+    /// Returns the 12 pentominoes named after the letters they resemble,
+    /// i.e. `F`, `I`, `L`, `N`, `P`, `T`, `U`, `V`, `W`, `X`, `Y` and `Z`.
+    pub fn standard_pentominoes() -> Vec<(&'static str, Polyomino)> {
+        let shapes: [(&'static str, &[&[u8]]); 12] = [
+            ("F", &[b".##", b"##.", b".#."]),
+            ("I", &[b"#####"]),
+            ("L", &[b"####", b"#..."]),
+            ("N", &[b".###", b"##.."]),
+            ("P", &[b"###", b".##"]),
+            ("T", &[b"###", b".#.", b".#."]),
+            ("U", &[b"#.#", b"###"]),
+            ("V", &[b"#..", b"#..", b"###"]),
+            ("W", &[b"#..", b"##.", b".##"]),
+            ("X", &[b".#.", b"###", b".#."]),
+            ("Y", &[b"####", b".#.."]),
+            ("Z", &[b"##.", b".#.", b".##"]),
+        ];
+
+        shapes.into_iter()
+            .map(|(name, shape)| (name, Polyomino::from_bytes_array(shape).unwrap()))
+            .collect()
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
     use super::*;
-    use crate::{Solver, SolverEvent};
+    use crate::Solver;
 
     fn compare_unique_orientations(piece: &Polyomino, expected: &[(bool, i32)]) {
         assert_eq!(
-            piece.unique_orientations().into_iter().collect::<HashSet<_>>(),
+            piece.unique_orientations().iter().copied().collect::<HashSet<_>>(),
             expected.iter()
                 .map(|&(f, r)| { Orientation { reflection: f, rotation: r } })
                 .collect::<HashSet<_>>()
         )
     }
 
+    #[test]
+    fn area_feasibility_can_be_checked() -> Result<(), Box<dyn Error>> {
+        let mut prob = PolyominoPacking::new();
+        prob.set_board("###\n#?#\n.##".parse()?);
+        assert_eq!(prob.board().empty_count(), 8);
+
+        let tromino: Polyomino = "###".parse()?;
+        assert_eq!(tromino.area(), 3);
+        prob.add_piece("I", tromino);
+        assert!(!prob.is_area_feasible()); // undershoots the 7 filled cells
+
+        prob.set_piece_range("I", 1, 2);
+        assert!(!prob.is_area_feasible());
+        prob.set_piece_range("I", 1, 3);
+        assert!(prob.is_area_feasible()); // 9 cells can cover 7 of them
+
+        prob.add_piece("O", "##\n##".parse()?);
+        assert!(prob.is_area_feasible());
+        prob.set_piece_range("I", 2, usize::MAX);
+        assert!(!prob.is_area_feasible()); // overshoots the 8 cells with 3 * 2 + 4 cells
+
+        Ok(())
+    }
+
+    #[test]
+    fn connectivity_can_be_checked() {
+        let v = |x, y| Vector2D { x, y };
+
+        let tromino_l = [v(0, 0), v(0, 1), v(1, 1)];
+        assert!(Polyomino::new(&tromino_l).unwrap().is_connected());
+        assert!(Polyomino::new_connected(&tromino_l).is_ok());
+
+        let diagonal = [v(0, 0), v(1, 1)];
+        assert!(!Polyomino::new(&diagonal).unwrap().is_connected());
+        assert_eq!(Polyomino::new_connected(&diagonal), Err(InvalidPieceError::Disconnected));
+
+        let unit = [v(3, -2)];
+        assert!(Polyomino::new(&unit).unwrap().is_connected());
+        assert!(Polyomino::new_connected(&unit).is_ok());
+
+        assert_eq!(Polyomino::new_connected(&[]), Err(InvalidPieceError::Blank));
+    }
+
+    #[test]
+    fn duplicate_cells_are_rejected() {
+        let v = |x, y| Vector2D { x, y };
+        assert_eq!(Polyomino::new(&[v(0, 0), v(0, 0)]), Err(InvalidPieceError::DuplicateCell));
+        assert_eq!(Polyomino::new(&[v(2, 1), v(3, 1), v(2, 1)]), Err(InvalidPieceError::DuplicateCell));
+        assert_eq!(Polyomino::new_connected(&[v(0, 0), v(1, 0), v(0, 0)]), Err(InvalidPieceError::DuplicateCell));
+        assert_eq!(Polyomino::new(&[v(2, 1), v(3, 1)]).unwrap().area(), 2);
+    }
+
+    #[test]
+    fn orientations_can_be_composed_and_inverted() {
+        let piece = Polyomino::from_bytes_array(&[b"###", b"#..", b"#.."]).unwrap();
+        let v = Vector2D { x: 2, y: 1 };
+
+        for a in Orientation::all() {
+            assert_eq!(a.compose(a.inverse()), Orientation::default());
+            assert_eq!(a.inverse().compose(a), Orientation::default());
+            assert_eq!(piece.orient(a).orient(a.inverse()), piece);
+
+            for b in Orientation::all() {
+                assert_eq!(a.compose(b).apply(v), b.apply(a.apply(v)));
+                assert_eq!(piece.orient(a).orient(b), piece.orient(a.compose(b)));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The board must not be empty, but it is 0 x 3")]
+    fn empty_rectangle_is_rejected() {
+        PolyominoPacking::<&str>::rectangle(0, 3);
+    }
+
     #[test]
     fn unique_orientations_can_be_found() {
         let tetro_l = Polyomino::from_bytes_array(&[b".#.", b".#.", b".##"]).unwrap();
@@ -400,8 +1285,526 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn unique_orientations_respect_symmetry() {
+        let one_sided = Polyomino::from_bytes_array(&[b".#.", b".#.", b".##"]).unwrap()
+            .with_symmetry(true, false);
+        compare_unique_orientations(&one_sided, &[
+            (false, 0), (false, 1), (false, 2), (false, 3),
+        ]);
+
+        let fixed = Polyomino::from_bytes_array(&[b".#.", b".#.", b".##"]).unwrap()
+            .with_symmetry(false, false);
+        compare_unique_orientations(&fixed, &[
+            (false, 0),
+        ]);
+    }
+
+    #[test]
+    fn orientations_can_be_restricted_globally() -> Result<(), Box<dyn Error>> {
+        let count = |allow_rotation: bool, allow_reflection: bool, one_sided: bool| -> Result<_, Box<dyn Error>> {
+            let mut prob = PolyominoPacking::new();
+            prob.set_board("####\n####".parse()?);
+            let piece: Polyomino = "###\n#..".parse()?;
+            prob.add_piece("L", if one_sided { piece.with_symmetry(true, false) } else { piece });
+            prob.set_piece_range("L", 2, 2);
+            prob.set_allow_rotation(allow_rotation);
+            prob.set_allow_reflection(allow_reflection);
+            Ok(Solver::new(prob.generate_problem()).solve_blocking()?.len())
+        };
+
+        // The two tilings are mirror images of each other
+        assert_eq!(count(true, true, false)?, 2);
+        assert_eq!(count(true, false, false)?, 1);
+        assert_eq!(count(true, false, false)?, count(true, true, true)?);
+        assert_eq!(count(false, true, false)?, 0);
+        assert_eq!(count(true, true, true)?, count(true, false, true)?);
+        Ok(())
+    }
+
+    #[test]
+    fn pieces_can_wrap_around_board() -> Result<(), Box<dyn Error>> {
+        let v = |x, y| Vector2D { x, y };
+        let subsets = |board: &str, wrap_x: bool, wrap_y: bool| -> Result<_, Box<dyn Error>> {
+            let mut prob = PolyominoPacking::new();
+            prob.set_board(board.parse()?);
+            prob.board_mut().set_wrap(wrap_x, wrap_y);
+            prob.add_piece("I", "###".parse()?);
+            Ok(prob.generate_problem().subsets().clone())
+        };
+        let horizontal = Orientation::default();
+
+        assert_eq!(subsets("####", false, false)?.len(), 2);
+        let wrapped = subsets("####", true, false)?;
+        assert_eq!(wrapped.len(), 4);
+        assert_eq!(wrapped[&("I", horizontal, v(3, 0))], vec![
+            CompoundConstraint::Piece("I"),
+            CompoundConstraint::Cell(v(3, 0)),
+            CompoundConstraint::Cell(v(0, 0)),
+            CompoundConstraint::Cell(v(1, 0)),
+        ]);
+
+        // Empty cells are still avoided after wrapping around
+        assert_eq!(subsets("##.#", true, false)?.keys().collect::<Vec<_>>(), vec![&("I", horizontal, v(3, 0))]);
+        // A piece cannot overlap itself, and the same cells are covered only once
+        assert_eq!(subsets("##", true, false)?.len(), 0);
+        assert_eq!(subsets("###", true, false)?.len(), 1);
+
+        // A piece as long as the torus covers the whole row (or column) from any translation
+        assert_eq!(subsets("###\n###\n###", false, false)?.len(), 6);
+        assert_eq!(subsets("###\n###\n###", true, true)?.len(), 6);
+        assert_eq!(subsets("####\n####\n####", false, false)?.len(), 6 + 4);
+        assert_eq!(subsets("####\n####\n####", true, true)?.len(), 12 + 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn adjacent_colors_must_match() -> Result<(), Box<dyn Error>> {
+        let domino: Polyomino = "##".parse()?;
+        assert_eq!(domino.unique_orientations().len(), 2);
+        let colored = "##".parse::<Polyomino>()?.with_colors(vec![1, 2]);
+        assert_eq!(colored.unique_orientations().len(), 4);
+        assert_eq!(colored.rotate(2).colors(), Some(&[2, 1][..]));
+
+        let solve = |colors: bool| -> Result<_, Box<dyn Error>> {
+            let mut prob = PolyominoPacking::new();
+            prob.set_board("######".parse()?);
+            for (name, piece_colors) in [("A", vec![1, 2]), ("B", vec![2, 3]), ("C", vec![3, 4])] {
+                prob.add_piece(name, "##".parse()?);
+                if colors { prob.set_piece_colors(name, piece_colors); }
+            }
+            Ok(Solver::new(prob.generate_problem()).solve_blocking()?)
+        };
+
+        // Only the chains 1-2 2-3 3-4 and its reverse remain
+        assert_eq!(solve(false)?.len(), 6);
+        let solutions = solve(true)?;
+        assert_eq!(solutions.len(), 2);
+        for sol in solutions {
+            let mut order: Vec<_> = sol.iter().map(|&(name, _, t)| (t.x, name)).collect();
+            order.sort();
+            let names: Vec<_> = order.into_iter().map(|(_, name)| name).collect();
+            assert!(names == ["A", "B", "C"] || names == ["C", "B", "A"]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn colored_pieces_can_wrap_around() -> Result<(), Box<dyn Error>> {
+        let solve = |wrap: bool| -> Result<_, Box<dyn Error>> {
+            let mut prob = PolyominoPacking::new();
+            prob.set_board("####".parse()?);
+            prob.board_mut().set_wrap(wrap, false);
+            for name in ["A", "B"] {
+                prob.add_piece(name, "##".parse()?);
+                prob.set_piece_colors(name, vec![1, 2]);
+            }
+            let prob = prob.generate_problem();
+            Ok((prob.subsets().len(), Solver::new(prob).solve_blocking()?.len()))
+        };
+
+        assert_eq!(solve(false)?, (12, 4));
+        // The same cells are covered in both directions, but with different colors
+        assert_eq!(solve(true)?, (16, 8));
+        Ok(())
+    }
+
+    #[test]
+    fn unique_orientations_are_cached() -> Result<(), Box<dyn Error>> {
+        let piece: Polyomino = ".#.\n.#.\n.##".parse()?;
+        assert_eq!(piece.unique_orientations(), piece.compute_unique_orientations());
+        assert_eq!(piece.unique_orientations().as_ptr(), piece.unique_orientations().as_ptr());
+
+        // Changing the symmetry flags invalidates the cache
+        let one_sided = piece.with_symmetry(true, false);
+        assert_eq!(one_sided.unique_orientations().len(), 4);
+        assert_eq!(one_sided.unique_orientations(), one_sided.compute_unique_orientations());
+
+        let mut prob = PolyominoPacking::new();
+        prob.set_board("###\n###\n###".parse()?);
+        prob.add_piece("U", "###\n#.#".parse()?);
+        prob.add_piece("T", "###\n.#.".parse()?);
+        let count = prob.generate_problem().subsets().len();
+        assert_eq!(count, 16);
+        assert_eq!(prob.generate_problem().subsets().len(), count);
+
+        Ok(())
+    }
+
+    #[test]
+    fn wildcard_cells_can_be_left_empty() -> Result<(), Box<dyn Error>> {
+        // Same as `examples/tetromino_multi.rs`
+        let mut prob = PolyominoPacking::default();
+        *prob.board_mut() = Board::from_bytes_array(&[
+            b"???.",
+            b"?##?",
+            b"?##?",
+        ]);
+        prob.add_piece("O", Polyomino::from_bytes_array(&[b"##", b"##"])?);
+        prob.set_piece_range("O", 0, 2);
+        prob.add_piece("T", Polyomino::from_bytes_array(&[b"###", b".#."])?);
+        prob.set_piece_range("T", 0, 2);
+
+        let solutions = Solver::new(prob.generate_problem()).solve_blocking()?;
+        assert_eq!(solutions.len(), 8);
+        for sol in solutions {
+            let cells: Vec<_> = sol.iter()
+                .flat_map(|(name, o, t)| prob.pieces()[name].orient(*o).translated_cells(*t))
+                .collect();
+            let unique_cells: HashSet<_> = cells.iter().collect();
+            assert_eq!(cells.len(), unique_cells.len());
+
+            for c in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+                assert!(unique_cells.contains(&Vector2D { x: c.0, y: c.1 }));
+            }
+            assert!(!unique_cells.contains(&Vector2D { x: 3, y: 0 }));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn piece_can_be_used_multiple_times() -> Result<(), Box<dyn Error>> {
+        let mut prob = PolyominoPacking::new();
+        prob.set_board(Board::from_bytes_array(&[b"####"]));
+        prob.add_piece("I", Polyomino::from_bytes_array(&[b"##"])?);
+        prob.set_piece_range("I", 0, 2);
+
+        let gen_prob = prob.generate_problem();
+        assert_eq!(gen_prob.constraints()[&CompoundConstraint::Piece("I")], (0, 2));
+
+        let solutions = Solver::new(gen_prob).solve_blocking()?;
+        assert_eq!(solutions.len(), 1);
+        let mut translations: Vec<_> = solutions[0].iter().map(|&(_, _, t)| t).collect();
+        translations.sort();
+        assert_eq!(translations, vec![Vector2D { x: 0, y: 0 }, Vector2D { x: 2, y: 0 }]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn board_can_be_parsed() {
+        let board: Board = "##\n#?#\n\n.#\n".parse().unwrap();
+        assert_eq!(board.size(), Vector2D { x: 3, y: 4 });
+        assert_eq!(board.cells(), &vec![
+            vec![Cell::Filled, Cell::Filled, Cell::Empty],
+            vec![Cell::Filled, Cell::Wildcard, Cell::Filled],
+            vec![Cell::Empty, Cell::Empty, Cell::Empty],
+            vec![Cell::Empty, Cell::Filled, Cell::Empty],
+        ]);
+
+        assert!("".parse::<Board>().is_err());
+        assert!("\n\n".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn piece_can_be_parsed() {
+        let piece: Polyomino = ".#\n###\n".parse().unwrap();
+        assert_eq!(piece, Polyomino::from_bytes_array(&[b".#.", b"###"]).unwrap());
+        assert!("..\n..".parse::<Polyomino>().is_err());
+    }
+
+    #[test]
+    fn standard_pentominoes_fit_rectangle() {
+        let mut prob = PolyominoPacking::rectangle(6, 10);
+        assert_eq!(prob.board().size(), Vector2D { x: 6, y: 10 });
+
+        let pentominoes = PolyominoPacking::standard_pentominoes();
+        assert_eq!(pentominoes.len(), 12);
+        assert!(pentominoes.iter().all(|(_, piece)| piece.area() == 5));
+        for (name, piece) in pentominoes {
+            prob.add_piece(name, piece);
+        }
+
+        // 12 piece constraints and 60 cell constraints
+        let problem = prob.generate_problem();
+        assert_eq!(problem.constraints().len(), 72);
+        assert!(prob.is_area_feasible());
+    }
+
+    #[test]
+    fn solution_can_be_rendered() -> Result<(), Box<dyn Error>> {
+        let mut prob = PolyominoPacking::new();
+        prob.set_board(".###\n.#?#".parse()?);
+        prob.add_piece("L", "##\n#".parse()?);
+        prob.add_piece("I", "#\n#".parse()?);
+
+        let sol = [
+            ("L", Orientation::default(), Vector2D { x: 1, y: 0 }),
+            ("I", Orientation::default(), Vector2D { x: 3, y: 0 }),
+        ];
+        let problem = prob.generate_problem();
+        assert!(sol.iter().all(|name| problem.subset(name).is_some()));
+        assert_eq!(prob.render_solution(&sol), "  L L I\n  L . I\n");
+        Ok(())
+    }
+
+    #[test]
+    fn identical_pieces_are_not_permuted() -> Result<(), Box<dyn Error>> {
+        let mut prob = PolyominoPacking::rectangle(3, 2);
+        prob.add_pieces("D", "##".parse()?, 3);
+        let solutions = Solver::new(prob.generate_problem()).solve_blocking()?;
+        assert_eq!(solutions.len(), 3);
+
+        // Distinct names count each tiling once per permutation of the dominoes
+        let mut distinct = PolyominoPacking::rectangle(3, 2);
+        for name in ["A", "B", "C"] {
+            distinct.add_piece(name, "##".parse()?);
+        }
+        let solutions = Solver::new(distinct.generate_problem()).solve_blocking()?;
+        assert_eq!(solutions.len(), 3 * 6);
+        Ok(())
+    }
+
+    #[test]
+    fn regions_restrict_the_placements() -> Result<(), Box<dyn Error>> {
+        let count = |prob: &PolyominoPacking<&str>| Solver::new(prob.generate_problem()).solve_blocking().unwrap().len();
+        let left = vec![Vector2D { x: 0, y: 0 }, Vector2D { x: 0, y: 1 }];
+
+        // The tilings by dominoes are |||, =| and |=
+        let mut prob = PolyominoPacking::rectangle(3, 2);
+        prob.add_pieces("D", "##".parse()?, 3);
+        assert_eq!(count(&prob), 3);
+
+        // =| overlaps the left column twice
+        assert_eq!(prob.board_mut().add_region(left.clone(), 0, 1), 0);
+        assert_eq!(count(&prob), 2);
+        assert_eq!(prob.board_mut().add_region(left.clone(), 2, 2), 1);
+        assert_eq!(count(&prob), 0);
+
+        // A region can forbid any piece on its cells
+        let mut prob = PolyominoPacking::rectangle(3, 2);
+        prob.add_pieces("D", "##".parse()?, 3);
+        prob.board_mut().add_region(vec![Vector2D { x: 2, y: 1 }], 0, 0);
+        assert_eq!(count(&prob), 0);
+
+        // Only the symmetries mapping each region onto a region are kept
+        let mut board = Board::new(vec![vec![Cell::Filled; 3]; 2]);
+        assert_eq!(board.symmetries().len(), 4);
+        board.add_region(left, 1, 1);
+        assert_eq!(board.symmetries().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn solution_cells_tile_the_board() -> Result<(), Box<dyn Error>> {
+        let board_cells = |prob: &PolyominoPacking<&str>| -> Vec<Vector2D> {
+            let Vector2D { x: width, y: height } = prob.board().size();
+            (0..height).flat_map(|y| (0..width).map(move |x| Vector2D { x, y })).collect()
+        };
+        let sorted_cells = |decoded: Vec<(&str, Vec<Vector2D>)>| {
+            let mut cells: Vec<_> = decoded.into_iter().flat_map(|(_, cells)| cells).collect();
+            cells.sort_by_key(|c| (c.y, c.x));
+            cells
+        };
+
+        let mut prob = PolyominoPacking::rectangle(10, 6);
+        for (name, piece) in PolyominoPacking::standard_pentominoes() {
+            prob.add_piece(name, piece);
+        }
+        let mut solver = Solver::new(prob.generate_problem());
+        let sol = solver.iter_blocking()?.next().unwrap();
+
+        let decoded = prob.solution_cells(&sol);
+        assert!(decoded.iter().map(|(name, _)| name).eq(sol.iter().map(|(name, _, _)| name)));
+        assert!(decoded.iter().all(|(_, cells)| cells.len() == 5));
+        assert_eq!(sorted_cells(decoded), board_cells(&prob));
+
+        // The cells are wrapped around the board
+        let mut prob = PolyominoPacking::rectangle(4, 1);
+        prob.board_mut().set_wrap(true, false);
+        prob.add_piece("I", "###".parse()?);
+        prob.add_piece("O", "#".parse()?);
+        let solutions = Solver::new(prob.generate_problem()).solve_blocking()?;
+        assert_eq!(solutions.len(), 4);
+        for sol in solutions {
+            assert_eq!(sorted_cells(prob.solution_cells(&sol)), board_cells(&prob));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn problem_can_be_generated_in_parallel() {
+        let assert_same = |prob: &PolyominoPacking<&str>| {
+            let (serial, parallel) = (prob.generate_problem(), prob.par_generate_problem());
+            // The equality of the problems does not depend on the insertion order
+            assert!(parallel.subset_names().eq(serial.subset_names()));
+            assert!(parallel.constraint_ranges().eq(serial.constraint_ranges()));
+            assert!(parallel == serial);
+        };
+
+        let mut prob = PolyominoPacking::rectangle(10, 6);
+        for (name, piece) in PolyominoPacking::standard_pentominoes() {
+            prob.add_piece(name, piece);
+        }
+        assert_same(&prob);
+
+        prob.board_mut().set_wrap(true, true);
+        prob.set_piece_colors("X", vec![1, 2, 3, 4, 5]);
+        assert_same(&prob);
+    }
+
+    #[test]
+    fn identical_pieces_can_be_deduplicated() -> Result<(), Box<dyn Error>> {
+        let count = |prob: &PolyominoPacking<&str>| Solver::new(prob.generate_problem()).solve_blocking().unwrap().len();
+        let t: Polyomino = "###\n.#".parse()?;
+
+        let mut prob = PolyominoPacking::rectangle(4, 4);
+        prob.add_piece("T1", t.rotate(0));
+        prob.add_piece("T2", t.rotate(1));
+        prob.add_piece("T3", t.reflect());
+        prob.add_piece("T4", "#\n##\n#".parse()?);
+        let permuted = count(&prob);
+        assert!(permuted > 0);
+
+        assert_eq!(prob.dedup_pieces(), vec![("T2", "T1"), ("T3", "T1"), ("T4", "T1")]);
+        assert!(prob.pieces().keys().eq(&["T1"]));
+        assert_eq!((prob.min["T1"], prob.max["T1"]), (4, 4));
+        assert_eq!(count(&prob) * 24, permuted);
+        assert!(prob.dedup_pieces().is_empty());
+
+        // Mirror images are only the same piece if they can be reflected
+        let s: Polyomino = ".##\n##".parse()?;
+        let mut prob = PolyominoPacking::rectangle(4, 4);
+        prob.add_piece("S", s.reflect());
+        prob.add_piece("Z", s.reflect().with_symmetry(true, false));
+        prob.add_piece("S'", s.with_symmetry(true, false));
+        assert_eq!(prob.dedup_pieces(), vec![]);
+        prob.set_allow_reflection(false);
+        assert_eq!(prob.dedup_pieces(), vec![("Z", "S")]);
+        Ok(())
+    }
+
+    #[test]
+    fn solution_can_be_exported_to_svg() -> Result<(), Box<dyn Error>> {
+        let mut prob = PolyominoPacking::new();
+        prob.set_board(".###\n.#?#".parse()?);
+        prob.add_piece("L", "##\n#".parse()?);
+        prob.add_piece("I", "#\n#".parse()?);
+
+        let sol = [
+            ("L", Orientation::default(), Vector2D { x: 1, y: 0 }),
+            ("I", Orientation::default(), Vector2D { x: 3, y: 0 }),
+        ];
+        let svg = prob.solution_to_svg(&sol, 10);
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert!(svg.contains(r#"width="40" height="20""#));
+        // 5 covered cells, 1 uncovered wildcard cell and the outline
+        assert_eq!(svg.matches("<rect ").count(), 7);
+        assert_eq!(svg.matches("<g ").count(), 3);
+        assert!(svg.contains(r#"<rect x="20" y="10" width="10" height="10"/>"#)); // the wildcard
+        Ok(())
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn solution_can_be_rendered_in_colors() -> Result<(), Box<dyn Error>> {
+        let mut prob = PolyominoPacking::new();
+        prob.set_board("####\n####".parse()?);
+        prob.add_piece("L", "##\n#".parse()?);
+        prob.add_piece("I", "#\n#".parse()?);
+        prob.add_piece("O", "#".parse()?);
+        prob.add_piece("T", "##".parse()?);
+
+        let sol = [
+            ("L", Orientation::default(), Vector2D { x: 0, y: 0 }),
+            ("I", Orientation::default(), Vector2D { x: 3, y: 0 }),
+            ("T", Orientation::default(), Vector2D { x: 1, y: 1 }),
+        ];
+        let rendered = prob.render_solution_ansi(&sol);
+        let colors = PolyominoPacking::<&str>::ANSI_COLORS;
+        // O is not used, so T keeps the fourth color
+        let used: Vec<_> = colors.iter()
+            .filter(|color| rendered.contains(&format!("\x1b[30;{}m", color)))
+            .collect();
+        assert_eq!(used, vec![&colors[0], &colors[1], &colors[3]]);
+        assert_eq!(rendered.matches("\x1b[0m").count(), 7);
+        assert!(rendered.contains(" . ")); // The uncovered cell is not colored
+        Ok(())
+    }
+
+    #[test]
+    fn board_symmetries_can_be_found() {
+        let square: Board = "###\n###\n###".parse().unwrap();
+        assert_eq!(square.symmetries().len(), 8);
+        let rectangle: Board = "###\n###".parse().unwrap();
+        assert_eq!(rectangle.symmetries().len(), 4);
+        let wildcard: Board = "?#?\n###".parse().unwrap();
+        assert_eq!(wildcard.symmetries().len(), 2);
+        let l_shape: Board = "##.\n###".parse().unwrap();
+        assert_eq!(l_shape.symmetries(), vec![Orientation::default()]);
+    }
+
+    #[test]
+    fn symmetric_solutions_can_be_removed() -> Result<(), Box<dyn Error>> {
+        let mut prob = PolyominoPacking::new();
+        prob.set_board("###\n###\n###".parse()?);
+        prob.add_piece("U", "###\n#.#".parse()?);
+        prob.add_piece("T", "###\n.#.".parse()?);
+
+        // The only packing, in all 4 rotations
+        let solutions = Solver::new(prob.generate_problem()).solve_blocking()?;
+        assert_eq!(solutions.len(), 4);
+        let unique = prob.dedup_symmetric_solutions(solutions.clone());
+        assert_eq!(unique, vec![solutions[0].clone()]);
+
+        // Multiple copies of the same piece: the two packings
+        // with a vertical pair of dominoes are mirror images of each other.
+        let mut prob = PolyominoPacking::new();
+        prob.set_board("##\n##\n##".parse()?);
+        prob.add_piece("I", "##".parse()?);
+        prob.set_piece_range("I", 3, 3);
+
+        let solutions = Solver::new(prob.generate_problem()).solve_blocking()?;
+        assert_eq!(solutions.len(), 3);
+        assert_eq!(prob.dedup_symmetric_solutions(solutions).len(), 2);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn blocked_board_has_no_solution() -> Result<(), Box<dyn Error>> {
+        use crate::SolverEvent;
+
+        // No domino fits in the board
+        let board = Board::from_bytes_array(&[
+            b"#.#",
+            b".#.",
+            b"#.#",
+        ]);
+
+        let mut prob = PolyominoPacking::new();
+        prob.set_board(board);
+        prob.add_piece("domino", Polyomino::from_bytes_array(&[b"##"])?);
+        prob.set_piece_range("domino", 0, 5);
+        let gen_prob = prob.generate_problem();
+        assert_eq!(gen_prob.subsets().len(), 0);
+        assert_eq!(gen_prob.uncoverable_constraints().len(), 5);
+
+        let mut solver = Solver::new(gen_prob);
+        solver.run()?;
+
+        let mut finished = None;
+        for event in solver {
+            match event {
+                SolverEvent::SolutionFound(_) => panic!("Unexpected solution"),
+                SolverEvent::Finished { solutions, .. } => finished = Some(solutions),
+                _ => (),
+            }
+        }
+        assert_eq!(finished, Some(0));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "threads")]
     #[test]
     fn problem_can_be_solved() -> Result<(), Box<dyn Error>> {
+        use crate::SolverEvent;
+
         let board = Board::from_bytes_array(&[
             b"###",
             b"###",
@@ -425,7 +1828,7 @@ mod tests {
         
         let mut solver = Solver::new(gen_prob);
         let mut solutions = vec![];
-        solver.run();
+        solver.run()?;
 
         for event in solver {
             if let SolverEvent::SolutionFound(sol) = event {
@@ -0,0 +1,190 @@
+//! A Sudoku puzzle.
+
+use crate::problem::Problem;
+
+/// A digit placed in a cell, represented as `(row, col, digit)`.
+/// It is used as a subset name of [`Problem`] instance.
+pub type Placement = (usize, usize, u8);
+
+/// An exact cover constraint for Sudoku.
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(test, derive(Debug))]
+pub enum SudokuConstraint {
+    /// The cell `(row, col)` contains a digit.
+    Cell(usize, usize),
+    /// The row contains the digit.
+    Row(usize, u8),
+    /// The column contains the digit.
+    Col(usize, u8),
+    /// The 3x3 box contains the digit. Boxes are numbered in row-major order.
+    Box(usize, u8),
+}
+
+/// A 9x9 Sudoku puzzle.
+/// 
+/// A puzzle may have several solutions.
+/// Keep iterating the solver to find all of them, e.g. to check whether the puzzle is unique.
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Sudoku {
+    grid: [[u8; 9]; 9],
+}
+
+impl Sudoku {
+    /// Creates a new puzzle from a grid of digits, where `0` represents a blank cell.
+    pub fn from_grid(grid: &[[u8; 9]; 9]) -> Sudoku {
+        assert!(grid.iter().flatten().all(|&d| d <= 9), "Sudoku digits must be in 0..=9");
+        Sudoku { grid: *grid }
+    }
+
+    /// Returns the grid of the puzzle, where `0` represents a blank cell.
+    pub fn grid(&self) -> &[[u8; 9]; 9] { &self.grid }
+
+    /// Generates an exact cover problem instance ([`Problem`]).
+    /// 
+    /// It has 324 constraints: 81 for each of cells, rows, columns, and boxes.
+    /// A given cell only has a single subset which places the given digit.
+    pub fn generate_problem(&self) -> Problem<Placement, SudokuConstraint> {
+        let mut prob = Problem::default();
+
+        for i in 0..9 {
+            for j in 0..9 {
+                prob.add_exact_constraint(SudokuConstraint::Cell(i, j));
+            }
+        }
+        for i in 0..9 {
+            for d in 1..=9 {
+                prob.add_exact_constraint(SudokuConstraint::Row(i, d));
+                prob.add_exact_constraint(SudokuConstraint::Col(i, d));
+                prob.add_exact_constraint(SudokuConstraint::Box(i, d));
+            }
+        }
+
+        for row in 0..9 {
+            for col in 0..9 {
+                let digits = match self.grid[row][col] {
+                    0 => 1..=9,
+                    d => d..=d,
+                };
+                for d in digits {
+                    prob.add_subset((row, col, d), vec![
+                        SudokuConstraint::Cell(row, col),
+                        SudokuConstraint::Row(row, d),
+                        SudokuConstraint::Col(col, d),
+                        SudokuConstraint::Box(row / 3 * 3 + col / 3, d),
+                    ]);
+                }
+            }
+        }
+
+        prob
+    }
+
+    /// Decodes a solution of the generated problem into a filled grid.
+    pub fn decode(&self, sol: &[Placement]) -> [[u8; 9]; 9] {
+        let mut grid = self.grid;
+        for &(row, col, d) in sol {
+            grid[row][col] = d;
+        }
+        grid
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+    use crate::Solver;
+
+    fn is_valid_solution(grid: &[[u8; 9]; 9]) -> bool {
+        let full: HashSet<u8> = (1..=9).collect();
+        (0..9).all(|i| {
+            let row: HashSet<_> = (0..9).map(|j| grid[i][j]).collect();
+            let col: HashSet<_> = (0..9).map(|j| grid[j][i]).collect();
+            let bx: HashSet<_> = (0..9).map(|j| grid[i / 3 * 3 + j / 3][i % 3 * 3 + j % 3]).collect();
+            row == full && col == full && bx == full
+        })
+    }
+
+    #[test]
+    fn unique_puzzle_can_be_solved() {
+        let sudoku = Sudoku::from_grid(&[
+            [5, 3, 0, 0, 7, 0, 0, 0, 0],
+            [6, 0, 0, 1, 9, 5, 0, 0, 0],
+            [0, 9, 8, 0, 0, 0, 0, 6, 0],
+            [8, 0, 0, 0, 6, 0, 0, 0, 3],
+            [4, 0, 0, 8, 0, 3, 0, 0, 1],
+            [7, 0, 0, 0, 2, 0, 0, 0, 6],
+            [0, 6, 0, 0, 0, 0, 2, 8, 0],
+            [0, 0, 0, 4, 1, 9, 0, 0, 5],
+            [0, 0, 0, 0, 8, 0, 0, 7, 9],
+        ]);
+
+        let solutions: Vec<_> = Solver::new(sudoku.generate_problem()).solve_blocking().unwrap()
+            .iter()
+            .map(|sol| sudoku.decode(sol))
+            .collect();
+
+        assert_eq!(solutions, vec![[
+            [5, 3, 4, 6, 7, 8, 9, 1, 2],
+            [6, 7, 2, 1, 9, 5, 3, 4, 8],
+            [1, 9, 8, 3, 4, 2, 5, 6, 7],
+            [8, 5, 9, 7, 6, 1, 4, 2, 3],
+            [4, 2, 6, 8, 5, 3, 7, 9, 1],
+            [7, 1, 3, 9, 2, 4, 8, 5, 6],
+            [9, 6, 1, 5, 3, 7, 2, 8, 4],
+            [2, 8, 7, 4, 1, 9, 6, 3, 5],
+            [3, 4, 5, 2, 8, 6, 1, 7, 9],
+        ]]);
+        assert!(is_valid_solution(&solutions[0]));
+    }
+
+    #[test]
+    fn uniqueness_can_be_checked() {
+        let mut grid = [
+            [5, 3, 0, 0, 7, 0, 0, 0, 0],
+            [6, 0, 0, 1, 9, 5, 0, 0, 0],
+            [0, 9, 8, 0, 0, 0, 0, 6, 0],
+            [8, 0, 0, 0, 6, 0, 0, 0, 3],
+            [4, 0, 0, 8, 0, 3, 0, 0, 1],
+            [7, 0, 0, 0, 2, 0, 0, 0, 6],
+            [0, 6, 0, 0, 0, 0, 2, 8, 0],
+            [0, 0, 0, 4, 1, 9, 0, 0, 5],
+            [0, 0, 0, 0, 8, 0, 0, 7, 9],
+        ];
+        let unique = |grid| Solver::new(Sudoku::from_grid(&grid).generate_problem()).has_unique_solution().unwrap();
+        assert!(unique(grid));
+        assert!(!unique([[0; 9]; 9]));
+
+        // Contradicts the only solution, which has 4 there
+        grid[0][2] = 1;
+        assert!(!unique(grid));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn blank_grid_has_multiple_solutions() {
+        use crate::SolverEvent;
+
+        let sudoku = Sudoku::default();
+        let prob = sudoku.generate_problem();
+        assert_eq!(prob.constraints().len(), 324);
+
+        let mut solver = Solver::new(prob);
+        let mut solutions = vec![];
+        solver.run().unwrap();
+
+        // The search space is huge, so stop after the first two solutions.
+        for event in solver {
+            if let SolverEvent::SolutionFound(sol) = event {
+                solutions.push(sudoku.decode(&sol));
+                if solutions.len() == 2 { break; }
+            }
+        }
+
+        assert_eq!(solutions.len(), 2);
+        assert_ne!(solutions[0], solutions[1]);
+        assert!(solutions.iter().all(is_valid_solution));
+    }
+}
@@ -1,23 +1,121 @@
 //! Provides a solver that solves a generic [`Problem`].
 
+use std::error::Error;
+#[cfg(feature = "threads")]
+use std::collections::BTreeSet;
+#[cfg(feature = "threads")]
+use std::cell::Cell;
+use std::fmt::{Debug, Display};
+#[cfg(feature = "threads")]
 use std::thread;
-use std::thread::{JoinHandle};
+#[cfg(feature = "threads")]
 use std::sync::mpsc;
-use std::sync::mpsc::{Sender, Receiver, TryRecvError, RecvError};
+#[cfg(feature = "threads")]
+use std::sync::mpsc::{Sender, SyncSender, Receiver, TryRecvError, RecvError, RecvTimeoutError, SendError, TrySendError};
+#[cfg(feature = "threads")]
+use std::sync::Arc;
+#[cfg(feature = "threads")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+#[cfg(feature = "threads")]
+use std::time::Instant;
 use crate::dlx::callback::{Callback};
+use crate::dlx::chooser::SmallestRow;
 // use crate::dlx::dlx::{Matrix};
-use crate::dlx::dlx_m::{Matrix};
-use crate::problem::{Problem, Value};
+use crate::dlx::dlx_m::{Matrix, SearchStep};
+#[cfg(feature = "threads")]
+use crate::dlx::dlx_m::{SearchStats};
+use crate::problem::{Problem, ProblemError, Value};
+use crate::trie::SolutionTrie;
 
 /// Events that a solver emits.
-pub enum SolverEvent<N: Value> {
+/// 
+/// The `Debug` output of an [`Aborted`](SolverEvent::Aborted) event
+/// only shows the size of the matrix instead of its contents.
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum SolverEvent<N: Value, E: Value> {
     SolutionFound(Vec<N>),
-    ProgressUpdated(f32),
+    /// The first solution of the search, e.g. to tell that the problem is solvable as soon as possible.
+    /// It is emitted only once, right before the `SolutionFound` (or `SolutionIndices`) of the same solution,
+    /// so the solutions can still be collected from `SolutionFound` alone.
+    /// A resumed search does not emit it if the solution had been found before it was aborted.
+    FirstSolutionFound(Vec<N>),
+    /// A solution given by the indices of its subsets in the insertion order,
+    /// emitted instead of `SolutionFound` if enabled by [`Solver::emit_indices`].
+    SolutionIndices(Vec<usize>),
+    /// An estimate of the fraction of the search that has been done (see [`Matrix::progress`]),
+    /// emitted on [`Solver::request_progress`].
+    /// It is also emitted right before `Aborted`, and right before `Finished` with `fraction` 1.
+    /// `eta` estimates the remaining time from the elapsed time,
+    /// and it is `None` until the search has made enough progress to tell.
+    ProgressUpdated { fraction: f32, eta: Option<Duration> },
+    /// The search has fully explored `branch` out of `total` top-level branches.
+    BranchAdvanced { branch: usize, total: usize },
+    /// The `row`-th subset is selected to cover the `column`-th constraint (both 0-based).
+    /// Only emitted if enabled by [`Solver::emit_search_events`].
+    Branch { column: usize, row: usize },
+    /// The last selected subset is unselected.
+    /// Only emitted if enabled by [`Solver::emit_search_events`].
+    Backtrack,
+    /// The search has backtracked, because the remaining subsets cannot cover a constraint
+    /// as many times as its minimum. It has the constraints not covered enough yet at that point.
+    /// Only emitted if enabled by [`Solver::emit_dead_ends`].
+    DeadEnd(Vec<E>),
     Paused,
-    Aborted(Matrix), // Solver can resume from here later
-    Finished,
+    Aborted(Matrix), // Solver can resume from here later (see `Solver::resume`)
+    /// The search has finished, with the statistics summed over all workers.
+    /// `solutions` is 0 iff the problem has no solution.
+    /// `max_depth` is the maximum depth of the search tree, at most the number of constraints.
+    Finished { solutions: usize, iterations: usize, backtracks: usize, max_depth: usize },
+}
+
+impl<N: Value + Debug, E: Value + Debug> Debug for SolverEvent<N, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverEvent::SolutionFound(sol) => f.debug_tuple("SolutionFound").field(sol).finish(),
+            SolverEvent::FirstSolutionFound(sol) => f.debug_tuple("FirstSolutionFound").field(sol).finish(),
+            SolverEvent::SolutionIndices(sol) => f.debug_tuple("SolutionIndices").field(sol).finish(),
+            SolverEvent::ProgressUpdated { fraction, eta } => f.debug_struct("ProgressUpdated")
+                .field("fraction", fraction)
+                .field("eta", eta)
+                .finish(),
+            SolverEvent::BranchAdvanced { branch, total } => f.debug_struct("BranchAdvanced")
+                .field("branch", branch)
+                .field("total", total)
+                .finish(),
+            SolverEvent::Branch { column, row } => f.debug_struct("Branch")
+                .field("column", column)
+                .field("row", row)
+                .finish(),
+            SolverEvent::Backtrack => f.write_str("Backtrack"),
+            SolverEvent::DeadEnd(elems) => f.debug_tuple("DeadEnd").field(elems).finish(),
+            SolverEvent::Paused => f.write_str("Paused"),
+            SolverEvent::Aborted(mat) => write!(f, "Aborted(Matrix {{ rows: {}, cols: {} }})", mat.row_count(), mat.col_count()),
+            SolverEvent::Finished { solutions, iterations, backtracks, max_depth } => f.debug_struct("Finished")
+                .field("solutions", solutions)
+                .field("iterations", iterations)
+                .field("backtracks", backtracks)
+                .field("max_depth", max_depth)
+                .finish(),
+        }
+    }
+}
+
+/// A transition of the search, reported by [`Solver::step`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepEvent<N, E> {
+    /// The constraint is chosen to branch on.
+    ConstraintChosen(E),
+    /// The subset is added to the partial solution.
+    SubsetSelected(N),
+    /// The subset is removed from the partial solution.
+    Backtracked(N),
+    SolutionFound(Vec<N>),
 }
 
+#[cfg(feature = "threads")]
+#[derive(Clone, Copy)]
 enum SolverThreadSignal {
     Run,
     RequestProgress,
@@ -25,18 +123,119 @@ enum SolverThreadSignal {
     Abort,
 }
 
+#[cfg(feature = "threads")]
+#[allow(clippy::large_enum_variant)]
 enum SolverThreadEvent {
     SolutionFound(Vec<usize>),
-    ProgressUpdated(f32),
+    FirstSolutionFound(Vec<usize>),
+    ProgressUpdated { fraction: f32, eta: Option<Duration> },
+    BranchAdvanced { branch: usize, total: usize },
+    Branch { column: usize, row: usize },
+    Backtrack,
+    DeadEnd(Vec<usize>),
+    Paused,
+    Aborted(Matrix),
+    Finished(SearchStats),
+}
+
+/// An error returned when a candidate solution is not valid.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CandidateError<N, E> {
+    /// The candidate contains a name that is not a subset of the problem.
+    UnknownSubset(N),
+    /// The candidate does not cover these constraints within their multiplicity ranges.
+    ViolatedConstraints(Vec<E>),
+    /// The problem itself is malformed.
+    InvalidProblem(ProblemError),
+}
+
+impl<N: Debug, E: Debug> Display for CandidateError<N, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CandidateError::UnknownSubset(name) => write!(f, "Unknown subset {:?}.", name),
+            CandidateError::ViolatedConstraints(elems) => write!(f, "Violated constraints {:?}.", elems),
+            CandidateError::InvalidProblem(err) => write!(f, "Invalid problem: {}", err),
+        }
+    }
+}
+
+impl<N: Debug, E: Debug> Error for CandidateError<N, E> {}
+
+/// The status of a [`Solver`] running in a background thread.
+/// 
+/// It is tracked by the signals sent to the solver thread and the events received from it,
+/// so a `Finished` or `Aborted` status is only observed after receiving the corresponding event.
+#[cfg(feature = "threads")]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SolverStatus {
+    /// The solver has not been run yet.
+    Idle,
+    Running,
     Paused,
-    _Aborted(Matrix),
+    /// A [`SolverEvent::Aborted`] event has been received.
+    Aborted,
+    /// A [`SolverEvent::Finished`] event has been received.
     Finished,
 }
 
+/// An error returned when a solver cannot be run or controlled.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SolverError {
+    /// The solver has not been run yet.
+    NotRunning,
+    /// The search has already finished or been aborted.
+    AlreadyFinished,
+    /// The solver thread has terminated unexpectedly.
+    ChannelClosed,
+    /// The problem is malformed.
+    InvalidProblem(ProblemError),
+}
+
+impl From<ProblemError> for SolverError {
+    fn from(err: ProblemError) -> SolverError {
+        SolverError::InvalidProblem(err)
+    }
+}
+
+impl Display for SolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverError::NotRunning => write!(f, "The solver is not running."),
+            SolverError::AlreadyFinished => write!(f, "The solver has already finished."),
+            SolverError::ChannelClosed => write!(f, "The solver thread has terminated unexpectedly."),
+            SolverError::InvalidProblem(err) => write!(f, "Invalid problem: {}", err),
+        }
+    }
+}
+
+impl Error for SolverError {}
+
 /// A solver for a [`Problem`] instance.
+/// 
+/// With the `threads` feature (enabled by default), [`run`](Solver::run) solves the problem
+/// in a background thread and the events can be received by iterating the solver.
+/// Otherwise, use the blocking methods such as [`solve_blocking`](Solver::solve_blocking),
+/// which run on the current thread without spawning any thread (e.g. in WASM).
 pub struct Solver<N: Value, E: Value> {
     problem: Problem<N, E>,
+    matrix: Option<Matrix>, // matrix to start from, generated on run if not given
+    max_depth: Option<usize>,
+    #[cfg(feature = "rand")]
+    seed: Option<u64>,
+    #[cfg(feature = "threads")]
     solver_thread: Option<SolverThread>,
+    #[cfg(feature = "threads")]
+    poll_interval: usize,
+    #[cfg(feature = "threads")]
+    channel_capacity: Option<usize>,
+    #[cfg(feature = "threads")]
+    emit_indices: bool,
+    #[cfg(feature = "threads")]
+    emit_search_events: bool,
+    #[cfg(feature = "threads")]
+    emit_dead_ends: bool,
+    #[cfg(feature = "threads")]
+    status: Cell<SolverStatus>,
 }
 
 impl<N: Value, E: Value> Solver<N, E> {
@@ -44,13 +243,45 @@ impl<N: Value, E: Value> Solver<N, E> {
     pub fn new(problem: Problem<N, E>) -> Solver<N, E> {
         Solver {
             problem,
+            matrix: None,
+            max_depth: None,
+            #[cfg(feature = "rand")]
+            seed: None,
+            #[cfg(feature = "threads")]
             solver_thread: None,
+            #[cfg(feature = "threads")]
+            poll_interval: 1,
+            #[cfg(feature = "threads")]
+            channel_capacity: None,
+            #[cfg(feature = "threads")]
+            emit_indices: false,
+            #[cfg(feature = "threads")]
+            emit_search_events: false,
+            #[cfg(feature = "threads")]
+            emit_dead_ends: false,
+            #[cfg(feature = "threads")]
+            status: Cell::new(SolverStatus::Idle),
         }
     }
+
+    /// Creates a solver that resumes the search from a matrix
+    /// emitted by a [`SolverEvent::Aborted`] event.
+    /// 
+    /// `problem` must be the problem that the aborted solver was solving,
+    /// so that the solutions can be mapped to the subset names again.
+    /// Only the solutions that were not found before the abort are emitted.
+    pub fn resume(problem: Problem<N, E>, matrix: Matrix) -> Solver<N, E> {
+        Solver { matrix: Some(matrix), ..Solver::new(problem) }
+    }
     
-    pub fn generate_matrix(problem: &Problem<N, E>) -> Matrix {
-        // TODO: validate problem
-        Solver::generate_multi_matrix(problem)
+    /// Generates a matrix of the problem, after validating it with [`Problem::validate`].
+    /// 
+    /// The forced subsets (see [`Problem::force_subset`]) are already selected in the matrix.
+    pub fn generate_matrix(problem: &Problem<N, E>) -> Result<Matrix, ProblemError> {
+        problem.validate()?;
+        let mut mat = Matrix::generate_unforced(problem);
+        mat.force_subsets(problem);
+        Ok(mat)
     }
 
     // TODO: use original algorithm if applicable
@@ -69,68 +300,595 @@ impl<N: Value, E: Value> Solver<N, E> {
     //     mat
     // }
 
-    fn generate_multi_matrix(problem: &Problem<N, E>) -> Matrix {
-        let constraints = problem.constraints();
-        let names = problem.subsets().keys();
-        let mut mat = Matrix::new(constraints.len());
+    /// Limits the number of subsets in a solution.
+    /// 
+    /// The search backtracks instead of selecting more than `max_depth` subsets,
+    /// so only the solutions within the limit are found.
+    /// It only takes effect before the solver starts solving.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = Some(max_depth);
+    }
+
+    /// Shuffles the order in which the subsets are tried, using a PRNG seeded with `seed`.
+    /// 
+    /// The solutions are the same, but their order is different across the seeds.
+    /// The order is reproducible given the same seed and the same version of `rand`.
+    /// It does not affect a solver created by [`resume`](Solver::resume).
+    #[cfg(feature = "rand")]
+    pub fn with_seed(mut self, seed: u64) -> Solver<N, E> {
+        self.seed = Some(seed);
+        self
+    }
 
-        for (e, &(min, max)) in constraints {
-            mat.set_multiplicity(constraints.get_index_of(e).unwrap() + 1, min, max);
+    /// Resets the solver so that it can solve the problem again from the start.
+    /// 
+    /// The solver thread is dropped, which aborts the search if it is still running,
+    /// and a fresh matrix is generated from the problem on the next run.
+    /// For a solver created by [`resume`](Solver::resume), the matrix to resume from is discarded as well.
+    pub fn reset(&mut self) {
+        self.matrix = None;
+        #[cfg(feature = "threads")]
+        {
+            self.solver_thread = None;
+            self.status.set(SolverStatus::Idle);
         }
+    }
 
-        for name in names {
-            let row: Vec<_> = problem.subsets()[name].iter()
-                .map(|e| { constraints.get_index_of(e).unwrap() + 1 })
-                .collect();
-            mat.add_row(&row);
+    /// Adds a subset to the problem, and resets the solver like [`reset`](Solver::reset).
+    /// 
+    /// Instead of generating the whole matrix again on the next run,
+    /// the subset is appended as a row to the matrix that the solver holds,
+    /// which is generated here if the solver does not hold one.
+    /// It is only done if the search has not started on the matrix yet,
+    /// and otherwise (e.g. the subset replaces an existing one, or some subsets are forced)
+    /// the matrix is generated from scratch on the next run.
+    /// Since the search is reset, it should not be called in the middle of a search
+    /// unless it is meant to be restarted.
+    pub fn add_subset_and_reset(&mut self, name: N, subset: Vec<E>) {
+        let row: Option<Vec<usize>> = subset.iter()
+            .map(|e| self.problem.constraints().get_index_of(e).map(|i| i + 1))
+            .collect();
+        let in_place = !subset.is_empty()
+            && !self.problem.subsets().contains_key(&name)
+            && self.problem.forced_subsets().is_empty()
+            && self.problem.uncoverable_constraints().is_empty();
+        #[cfg(feature = "rand")]
+        let in_place = in_place && self.seed.is_none(); // the new row would not be shuffled
+
+        let mat = match self.matrix.take() {
+            Some(mat) => Some(mat),
+            None if in_place => self.build_matrix().ok(),
+            None => None,
+        };
+        self.reset();
+        self.problem.add_subset(name, subset);
+
+        if let (Some(mut mat), Some(row), true) = (mat, row, in_place) {
+            if mat.stats().iterations == 0 && mat.partial_solution().is_empty() {
+                mat.add_row(&row).unwrap();
+                self.matrix = Some(mat);
+            }
+        }
+    }
+
+    /// Takes the matrix to resume from, or generates a new one,
+    /// and applies the search settings to it.
+    fn take_matrix(&mut self) -> Result<Matrix, ProblemError> {
+        let mut mat = match self.matrix.take() {
+            Some(mat) => mat,
+            None => self.build_matrix()?,
+        };
+        if self.max_depth.is_some() { mat.set_max_depth(self.max_depth); }
+        Ok(mat)
+    }
+
+    /// Generates a matrix of the problem.
+    /// 
+    /// If the problem has an uncoverable constraint (see [`Problem::uncoverable_constraints`]),
+    /// it skips building the whole matrix and returns a trivially infeasible one,
+    /// so that the search finishes immediately without any solution.
+    /// Its dead end still reports the uncoverable constraints.
+    fn build_matrix(&self) -> Result<Matrix, ProblemError> {
+        self.problem.validate()?;
+        let uncoverable = self.problem.uncoverable_constraints();
+        if !uncoverable.is_empty() {
+            // The same columns without any rows, where only the uncoverable ones are primary,
+            // and empty rows to keep the row indices valid
+            let mut mat = Matrix::new(self.problem.constraints().len());
+            for (i, elem) in self.problem.constraints().keys().enumerate() {
+                if !uncoverable.contains(elem) { mat.set_secondary(i + 1); }
+            }
+            for _ in 0..self.problem.subsets().len() {
+                mat.add_row(&[]).unwrap();
+            }
+            return Ok(mat);
+        }
+
+        let mut mat = Matrix::generate_unforced(&self.problem);
+        #[cfg(feature = "rand")]
+        if let Some(seed) = self.seed {
+            use rand::SeedableRng;
+            mat.shuffle_rows(&mut rand::rngs::StdRng::seed_from_u64(seed));
+        }
+        mat.force_subsets(&self.problem);
+        Ok(mat)
+    }
+
+    /// Solves the problem on the current thread, and returns all solutions.
+    /// 
+    /// It does not spawn any thread, so it also works in single-threaded environments.
+    pub fn solve_blocking(self) -> Result<Vec<Vec<N>>, ProblemError> {
+        let mut solutions = vec![];
+        self.solve_blocking_with(|sol| solutions.push(sol))?;
+        Ok(solutions)
+    }
+
+    /// Solves the problem on the current thread, calling `on_solution` for each solution.
+    /// 
+    /// Unlike [`solve_blocking`](Solver::solve_blocking),
+    /// it does not keep the solutions in memory.
+    pub fn solve_blocking_with(mut self, mut on_solution: impl FnMut(Vec<N>)) -> Result<(), ProblemError> {
+        let mut mat = self.take_matrix()?;
+        mat.solve(&mut FnCallback(|sol: Vec<usize>| on_solution(self.decode_rows(&sol))));
+        Ok(())
+    }
+
+    /// Runs the solver to the end and stores all solutions in a [`SolutionTrie`].
+    /// 
+    /// Solutions sharing a common prefix share memory in the trie,
+    /// so it is suitable for problems with a huge number of solutions.
+    pub fn solve_into_trie(mut self) -> Result<SolutionTrie<N>, ProblemError> {
+        let mut trie = SolutionTrie::new(self.problem.subsets().keys().cloned().collect());
+        let mut mat = self.take_matrix()?;
+        mat.solve(&mut FnCallback(|sol: Vec<usize>| trie.insert(sol.iter().map(|x| x - 1))));
+        Ok(trie)
+    }
+
+    /// Returns whether the problem has exactly one solution, e.g. to check that a puzzle is well-posed.
+    /// 
+    /// The search runs in the current thread and stops as soon as a second solution is found,
+    /// so it does not enumerate all solutions of a problem with many of them.
+    pub fn has_unique_solution(mut self) -> Result<bool, ProblemError> {
+        Ok(self.iter_blocking()?.take(2).count() == 1)
+    }
+
+    /// Finds a solution with the minimum total cost of subsets (see [`Problem::add_weighted_subset`]),
+    /// and returns it with its cost. It returns `None` if there is no solution.
+    /// 
+    /// It runs a branch and bound search in the current thread:
+    /// once a solution is found, partial solutions costing at least as much are pruned.
+    /// If several solutions have the minimum cost, the first one found is returned.
+    pub fn solve_minimum(mut self) -> Result<Option<(Vec<N>, usize)>, ProblemError> {
+        let mut mat = self.take_matrix()?;
+        for (i, name) in self.problem.subsets().keys().enumerate() {
+            mat.set_row_cost(i + 1, self.problem.cost(name));
+        }
+
+        let mut callback = MinimumCostCallback::default();
+        mat.solve(&mut callback);
+        Ok(callback.best.map(|(sol, cost)| (self.decode_rows(&sol), cost)))
+    }
+
+    /// Finds the lexicographically smallest solution, comparing the solutions as sorted lists of subset indices.
+    /// It returns the solution in the subset order, or `None` if there is no solution.
+    /// 
+    /// It runs a branch and bound search in the current thread, branching on the constraint
+    /// of the first remaining subset (see [`SmallestRow`]) to find small solutions early.
+    /// Once a solution is found, a partial solution is pruned if it cannot be completed
+    /// to a smaller one, judging from its subsets and the remaining ones.
+    pub fn min_solution(mut self) -> Result<Option<Vec<N>>, ProblemError> {
+        let mut mat = self.take_matrix()?;
+        let mut callback = LexMinCallback::default();
+        mat.solve_with(&mut callback, &SmallestRow);
+        Ok(callback.best.map(|sol| self.decode_rows(&sol)))
+    }
+
+    /// Searches for a single solution with randomized restarts, using a PRNG seeded with `seed`.
+    /// It returns `None` if the problem has no solution, or if no solution is found within `max_restarts` restarts.
+    /// 
+    /// Each attempt shuffles the subsets (as [`with_seed`](Solver::with_seed) does) and dives into the search,
+    /// until it backtracks more than its budget and restarts with another shuffle.
+    /// The budget doubles on every restart, so a few unlucky dives do not take much time,
+    /// while a hard problem is eventually searched exhaustively if the restarts are enough.
+    /// It is meant to find *a* solution of a huge problem quickly, rather than to enumerate them.
+    /// The search runs in the current thread, and it ignores the seed given by `with_seed`.
+    #[cfg(feature = "rand")]
+    pub fn find_one_randomized(self, seed: u64, max_restarts: usize) -> Result<Option<Vec<N>>, ProblemError> {
+        use rand::SeedableRng;
+
+        self.problem.validate()?;
+        if !self.problem.uncoverable_constraints().is_empty() { return Ok(None); }
+        let base = Matrix::generate_unforced(&self.problem);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+        for restart in 0..=max_restarts {
+            let mut mat = base.clone();
+            mat.shuffle_rows(&mut rng);
+            mat.force_subsets(&self.problem);
+            if self.max_depth.is_some() { mat.set_max_depth(self.max_depth); }
+
+            let shift = restart.min(usize::BITS as usize - 1) as u32;
+            let mut callback = RestartCallback {
+                max_backtracks: RestartCallback::BASE_BACKTRACKS.saturating_mul(1 << shift),
+                ..RestartCallback::default()
+            };
+            mat.solve(&mut callback);
+
+            if let Some(sol) = callback.solution { return Ok(Some(self.decode_rows(&sol))); }
+            // The whole search tree has been explored without any solution
+            if !callback.restarted { return Ok(None); }
+        }
+        Ok(None)
+    }
+
+    /// Verifies that `candidate` is a solution, and then finds all the other solutions.
+    /// 
+    /// Solutions are compared regardless of the order of subsets.
+    /// It returns an error describing why the candidate is invalid, if it is.
+    pub fn verify_and_continue(mut self, candidate: Vec<N>) -> Result<Vec<Vec<N>>, CandidateError<N, E>> {
+        let mut expected = vec![];
+        for name in &candidate {
+            match self.problem.subsets().get_index_of(name) {
+                Some(idx) => expected.push(idx + 1),
+                None => return Err(CandidateError::UnknownSubset(name.clone())),
+            }
+        }
+        expected.sort_unstable();
+
+        let violated = self.problem.violated_constraints(&candidate);
+        if !violated.is_empty() {
+            return Err(CandidateError::ViolatedConstraints(violated));
+        }
+
+        let mut mat = self.take_matrix().map_err(CandidateError::InvalidProblem)?;
+        let mut solutions = vec![];
+        mat.solve(&mut FnCallback(|mut sol: Vec<usize>| {
+            let names = self.decode_rows(&sol);
+            sol.sort_unstable();
+            if sol != expected { solutions.push(names); }
+        }));
+        Ok(solutions)
+    }
+
+    /// Returns an iterator of solutions that advances the search lazily on the current thread.
+    /// 
+    /// Each call of `next` searches only until the next solution is found,
+    /// so the search stops as soon as the iterator is dropped.
+    pub fn iter_blocking(&mut self) -> Result<BlockingIter<'_, N, E>, ProblemError> {
+        let matrix = self.take_matrix()?;
+        Ok(BlockingIter { solver: self, matrix: Some(matrix) })
+    }
+
+    /// Returns the names of the subsets at `indices` in the insertion order,
+    /// e.g. to decode a [`SolverEvent::SolutionIndices`] event.
+    pub fn decode(&self, indices: &[usize]) -> Vec<N> {
+        indices.iter()
+            .map(|&i| { self.problem.subsets().get_index(i).unwrap().0.clone() })
+            .collect()
+    }
+
+    /// Advances the search on the current thread until the next transition, and returns it,
+    /// e.g. to visualize the search one decision at a time.
+    /// 
+    /// It returns `None` once the search has finished.
+    /// The other solving methods continue the search from the current step.
+    pub fn step(&mut self) -> Result<Option<StepEvent<N, E>>, ProblemError> {
+        let mut mat = self.take_matrix()?;
+        let step = mat.step();
+        self.matrix = Some(mat);
+
+        let subset = |row: usize| self.problem.subsets().get_index(row - 1).unwrap().0.clone();
+        Ok(step.map(|step| match step {
+            SearchStep::ColumnChosen(col) =>
+                StepEvent::ConstraintChosen(self.problem.constraints().get_index(col - 1).unwrap().0.clone()),
+            SearchStep::RowSelected(row) => StepEvent::SubsetSelected(subset(row)),
+            SearchStep::Backtracked(row) => StepEvent::Backtracked(subset(row)),
+            SearchStep::SolutionFound(sol) => StepEvent::SolutionFound(self.decode_rows(&sol)),
+        }))
+    }
+
+    fn decode_rows(&self, sol: &[usize]) -> Vec<N> {
+        sol.iter()
+            .map(|x| { self.problem.subsets().get_index(x-1).unwrap().0.clone() })
+            .collect()
+    }
+}
+
+/// An iterator of solutions that advances the search on the current thread.
+/// See [`Solver::iter_blocking`].
+pub struct BlockingIter<'a, N: Value, E: Value> {
+    solver: &'a Solver<N, E>,
+    matrix: Option<Matrix>, // None after the search has finished
+}
+
+impl<N: Value, E: Value> Iterator for BlockingIter<'_, N, E> {
+    type Item = Vec<N>;
+
+    fn next(&mut self) -> Option<Vec<N>> {
+        let mat = self.matrix.as_mut()?;
+        let mut callback = StepCallback::default();
+        mat.solve(&mut callback);
+
+        match callback.solution {
+            Some(sol) => Some(self.solver.decode_rows(&sol)),
+            None => {
+                self.matrix = None;
+                None
+            }
         }
-        mat
     }
+}
 
-    fn send_signal(&self, signal: SolverThreadSignal) -> Result<(), ()> {
-        let thread = self.solver_thread.as_ref().ok_or(())?;
+#[cfg(feature = "threads")]
+impl<N: Value, E: Value> Solver<N, E> {
+    fn send_signal(&self, signal: SolverThreadSignal) -> Result<(), SolverError> {
+        let thread = self.solver_thread.as_ref().ok_or(SolverError::NotRunning)?;
+        // The workers may not have hung up yet, even after the last event is received
+        if matches!(self.status.get(), SolverStatus::Finished | SolverStatus::Aborted) {
+            return Err(SolverError::AlreadyFinished);
+        }
         thread.send(signal)
     }
 
-    /// Runs the solver thread.
-    pub fn run(&mut self) {
-        // TODO: where should I handle thread SendError?
-        if let Some(thread) = &self.solver_thread {
-            thread.send(SolverThreadSignal::Run).ok();
+    /// Runs the solver thread, or resumes it if it is paused.
+    /// 
+    /// It returns an error if the problem is malformed (see [`Problem::validate`]),
+    /// or if the search has already finished.
+    pub fn run(&mut self) -> Result<(), SolverError> {
+        self.run_parallel(1)
+    }
+
+    /// Runs the solver on `num_threads` worker threads.
+    /// 
+    /// The top-level branches of the search are distributed across the workers,
+    /// and the events are merged into the same event stream.
+    /// The solutions are the same as [`run`](Solver::run), but their order is not deterministic.
+    /// 
+    /// Each worker emits its own [`SolverEvent::Paused`] and [`SolverEvent::Aborted`] event,
+    /// and a single [`SolverEvent::Finished`] is emitted after all workers have finished.
+    /// An aborted worker can be resumed separately with [`Solver::resume`].
+    pub fn run_parallel(&mut self, num_threads: usize) -> Result<(), SolverError> {
+        self.start(num_threads, &mut |job| { thread::spawn(job); })
+    }
+
+    /// Runs the solver like [`run`](Solver::run), but on a thread provided by the caller,
+    /// e.g. a thread pool shared between many solvers, instead of spawning a new thread.
+    /// 
+    /// `spawn` is called once with the search job, which must be run on another thread
+    /// (running it on the current thread would block until the search finishes or is aborted).
+    /// The events are received in the same way as [`run`](Solver::run).
+    /// Note that a paused search keeps occupying the thread until it is resumed or aborted.
+    /// 
+    /// ```
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    /// use exact_cover::{Problem, Solver, SolverEvent};
+    /// use exact_cover::solver::SolverJob;
+    /// 
+    /// // A minimal pool with a single reusable worker
+    /// let (pool, jobs) = mpsc::channel::<SolverJob>();
+    /// thread::spawn(move || for job in jobs { job() });
+    /// 
+    /// let mut prob = Problem::default();
+    /// prob.add_exact_constraints([1, 2]);
+    /// prob.add_subset("A", vec![1, 2]);
+    /// 
+    /// let mut solver = Solver::new(prob);
+    /// solver.run_on(|job| pool.send(job).unwrap()).unwrap();
+    /// let solutions: Vec<_> = solver.into_iter()
+    ///     .filter(|e| matches!(e, SolverEvent::SolutionFound(_)))
+    ///     .collect();
+    /// assert_eq!(solutions.len(), 1);
+    /// ```
+    pub fn run_on(&mut self, mut spawn: impl FnMut(SolverJob)) -> Result<(), SolverError> {
+        self.start(1, &mut spawn)
+    }
+
+    fn start(&mut self, num_threads: usize, spawn: &mut dyn FnMut(SolverJob)) -> Result<(), SolverError> {
+        if self.solver_thread.is_some() {
+            self.send_signal(SolverThreadSignal::Run)?;
         } else {
-            let mat = Solver::generate_matrix(&self.problem);
-            self.solver_thread = Some(SolverThread::new(mat));
+            let mat = self.take_matrix()?;
+            let settings = ThreadSettings {
+                poll_interval: self.poll_interval,
+                capacity: self.channel_capacity,
+                search_events: self.emit_search_events,
+                dead_ends: self.emit_dead_ends,
+            };
+            self.solver_thread = Some(SolverThread::new(mat, num_threads.max(1), settings, spawn));
         }
+        self.status.set(SolverStatus::Running);
+        Ok(())
+    }
+
+    /// Returns the current status of the solver.
+    pub fn status(&self) -> SolverStatus { self.status.get() }
+
+    /// Makes the solver thread check for signals only every `interval` iterations (1 by default).
+    /// 
+    /// A larger interval reduces the overhead of each search step,
+    /// but [`pause`](Solver::pause) and progress requests
+    /// may take up to `interval` iterations to be handled.
+    /// [`abort`](Solver::abort) is still handled at the next iteration,
+    /// through a flag checked at every iteration in addition to the signals.
+    /// It only takes effect before the solver starts running.
+    pub fn set_poll_interval(&mut self, interval: usize) {
+        self.poll_interval = interval.max(1);
+    }
+
+    /// Limits the number of events buffered between the solver thread and the consumer.
+    /// 
+    /// When the buffer is full, the solver thread waits until the consumer receives an event,
+    /// so the search is throttled to the consumption rate instead of piling up the solutions in memory.
+    /// It still handles [`pause`](Solver::pause) and [`abort`](Solver::abort) while waiting.
+    /// The events are not buffered at all if `capacity` is 0.
+    /// Note that a stream from `into_stream` still buffers the events without a limit.
+    /// It only takes effect before the solver starts running.
+    pub fn set_channel_capacity(&mut self, capacity: usize) {
+        self.channel_capacity = Some(capacity);
+    }
+
+    /// Makes the solver emit [`SolverEvent::SolutionIndices`] instead of [`SolverEvent::SolutionFound`].
+    /// 
+    /// It saves cloning the subset names of every solution,
+    /// and the indices can be decoded later by [`decode`](Solver::decode) if needed.
+    /// It does not affect [`solve_all`](Solver::solve_all) and [`first_solution`](Solver::first_solution).
+    pub fn emit_indices(&mut self, enabled: bool) {
+        self.emit_indices = enabled;
+    }
+
+    /// Makes the solver emit [`SolverEvent::Branch`] and [`SolverEvent::Backtrack`] events,
+    /// from which the whole search tree can be reconstructed.
+    /// 
+    /// They are not emitted by default, since there are far more of them than the solutions.
+    /// It only takes effect before the solver starts running.
+    pub fn emit_search_events(&mut self, enabled: bool) {
+        self.emit_search_events = enabled;
+    }
+
+    /// Makes the solver emit a [`SolverEvent::DeadEnd`] event
+    /// whenever the search gives up a branch because some constraint cannot be covered anymore.
+    /// 
+    /// It helps to find out why a problem has no solutions.
+    /// It only takes effect before the solver starts running.
+    pub fn emit_dead_ends(&mut self, enabled: bool) {
+        self.emit_dead_ends = enabled;
+    }
+
+    /// Runs the solver to the end and returns all solutions.
+    /// 
+    /// The search runs on the solver thread, and the other events are discarded.
+    /// See [`solve_blocking`](Solver::solve_blocking) to solve on the current thread.
+    pub fn solve_all(mut self) -> Result<Vec<Vec<N>>, SolverError> {
+        self.emit_indices = false;
+        self.run()?;
+        Ok(self.into_iter()
+            .filter_map(|event| match event {
+                SolverEvent::SolutionFound(sol) => Some(sol),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Runs the solver and returns an iterator of the solutions, as they are found.
+    /// 
+    /// Unlike iterating over the [`SolverEvent`]s, the other events are skipped,
+    /// and the iterator ends when the search finishes.
+    /// The search continues in the background while the solutions are consumed.
+    pub fn solutions(mut self) -> Result<impl Iterator<Item = Vec<N>>, SolverError> {
+        self.emit_indices = false;
+        self.run()?;
+        Ok(self.into_iter()
+            .take_while(|event| !matches!(event, SolverEvent::Finished { .. }))
+            .filter_map(|event| match event {
+                SolverEvent::SolutionFound(sol) => Some(sol),
+                _ => None,
+            }))
+    }
+
+    /// Runs the solver to the end and returns all solutions as sets of subset names,
+    /// sorted in ascending order.
+    /// 
+    /// Unlike [`solve_all`](Solver::solve_all), the result does not depend on the order
+    /// in which the subsets are selected or the solutions are found,
+    /// so it can be compared across runs, e.g. with different seeds or column choosers.
+    pub fn solve_all_as_sets(self) -> Result<Vec<BTreeSet<N>>, SolverError> where N: Ord {
+        let mut solutions: Vec<BTreeSet<N>> = self.solve_all()?.into_iter()
+            .map(|sol| sol.into_iter().collect())
+            .collect();
+        solutions.sort_unstable();
+        Ok(solutions)
+    }
+
+    /// Runs the solver until it finds a solution, and returns it.
+    /// It returns `None` if the problem has no solution.
+    /// 
+    /// The solver is aborted as soon as the first solution is found.
+    pub fn first_solution(mut self) -> Result<Option<Vec<N>>, SolverError> {
+        self.emit_indices = false;
+        self.run()?;
+        let mut events = self.into_iter();
+        let first = events.find_map(|event| match event {
+            SolverEvent::SolutionFound(sol) => Some(sol),
+            _ => None,
+        });
+        events.solver().abort().ok(); // the search may have already finished
+        Ok(first)
+    }
+
+    /// Requests the solver thread to emit a [`SolverEvent::ProgressUpdated`] event.
+    pub fn request_progress(&self) -> Result<(), SolverError> { self.send_signal(SolverThreadSignal::RequestProgress) }
+    /// Pauses the solver thread. Call [`run`](Solver::run) to resume it.
+    pub fn pause(&self) -> Result<(), SolverError> {
+        self.send_signal(SolverThreadSignal::Pause)?;
+        self.status.set(SolverStatus::Paused);
+        Ok(())
+    }
+    /// Aborts the solver thread, which emits the search state in a [`SolverEvent::Aborted`] event.
+    /// 
+    /// Unlike the other signals, it takes effect at the next iteration regardless of the poll interval
+    /// (see [`set_poll_interval`](Solver::set_poll_interval)).
+    pub fn abort(&self) -> Result<(), SolverError> {
+        let thread = self.solver_thread.as_ref().ok_or(SolverError::NotRunning)?;
+        thread.abort.store(true, Ordering::Relaxed);
+        self.send_signal(SolverThreadSignal::Abort)
     }
-    pub fn request_progress(&self) { self.send_signal(SolverThreadSignal::RequestProgress).ok(); }
-    pub fn pause(&self) { self.send_signal(SolverThreadSignal::Pause).ok(); }
-    pub fn abort(&self) { self.send_signal(SolverThreadSignal::Abort).ok(); }
 
-    fn map_event(&self, event: SolverThreadEvent) -> SolverEvent<N> {
+    fn map_event(&self, event: SolverThreadEvent) -> SolverEvent<N, E> {
+        match event {
+            SolverThreadEvent::Aborted(_) => self.status.set(SolverStatus::Aborted),
+            SolverThreadEvent::Finished(_) => self.status.set(SolverStatus::Finished),
+            _ => (),
+        }
+
         match event {
-            SolverThreadEvent::SolutionFound(sol) => SolverEvent::SolutionFound(
-                sol.iter()
-                    .map(|x| { self.problem.subsets().get_index(x-1).unwrap().0.clone() })
-                    .collect()
-            ),
-            SolverThreadEvent::ProgressUpdated(progress) => SolverEvent::ProgressUpdated(progress),
+            SolverThreadEvent::SolutionFound(mut sol) if self.emit_indices => {
+                for row in &mut sol { *row -= 1; }
+                SolverEvent::SolutionIndices(sol)
+            }
+            SolverThreadEvent::SolutionFound(sol) => SolverEvent::SolutionFound(self.decode_rows(&sol)),
+            SolverThreadEvent::FirstSolutionFound(sol) => SolverEvent::FirstSolutionFound(self.decode_rows(&sol)),
+            SolverThreadEvent::ProgressUpdated { fraction, eta } => SolverEvent::ProgressUpdated { fraction, eta },
+            SolverThreadEvent::BranchAdvanced { branch, total } => SolverEvent::BranchAdvanced { branch, total },
+            SolverThreadEvent::Branch { column, row } => SolverEvent::Branch { column, row },
+            SolverThreadEvent::Backtrack => SolverEvent::Backtrack,
+            SolverThreadEvent::DeadEnd(cols) => SolverEvent::DeadEnd(cols.iter()
+                .map(|&c| self.problem.constraints().get_index(c - 1).unwrap().0.clone())
+                .collect()),
             SolverThreadEvent::Paused => SolverEvent::Paused,
-            SolverThreadEvent::_Aborted(mat) => SolverEvent::Aborted(mat),
-            SolverThreadEvent::Finished => SolverEvent::Finished,
+            SolverThreadEvent::Aborted(mat) => SolverEvent::Aborted(mat),
+            SolverThreadEvent::Finished(stats) => SolverEvent::Finished {
+                solutions: stats.solutions,
+                iterations: stats.iterations,
+                backtracks: stats.backtracks,
+                max_depth: stats.max_depth,
+            },
         }
     }
 }
 
 /// An iterator of [`SolverEvent`]s that a solver emits.
+#[cfg(feature = "threads")]
 pub struct SolverIter<N: Value, E: Value> {
     solver: Solver<N, E>,
 }
 
+#[cfg(feature = "threads")]
+impl<N: Value, E: Value> SolverIter<N, E> {
+    /// Returns a reference to the solver, e.g. to pause or abort it while iterating.
+    pub fn solver(&self) -> &Solver<N, E> { &self.solver }
+    /// Returns a mutable reference to the solver, e.g. to resume it by [`run`](Solver::run) after a pause.
+    pub fn solver_mut(&mut self) -> &mut Solver<N, E> { &mut self.solver }
+    /// Returns the solver back, e.g. to [`reset`](Solver::reset) it after iterating.
+    pub fn into_solver(self) -> Solver<N, E> { self.solver }
+}
+
+#[cfg(feature = "threads")]
 impl<N: Value, E: Value> Iterator for SolverIter<N, E> {
-    type Item = SolverEvent<N>;
+    type Item = SolverEvent<N, E>;
 
-    fn next(&mut self) -> Option<SolverEvent<N>> {
-        if let Ok(e) = self.solver.solver_thread.as_ref()?.recv() {
+    fn next(&mut self) -> Option<SolverEvent<N, E>> {
+        if let Ok(e) = self.solver.solver_thread.as_mut()?.recv() {
             Some(self.solver.map_event(e))
         } else {
             None
@@ -138,9 +896,9 @@ impl<N: Value, E: Value> Iterator for SolverIter<N, E> {
     }
 }
 
-// TODO: also provide stream
+#[cfg(feature = "threads")]
 impl<N: Value, E: Value> IntoIterator for Solver<N, E> {
-    type Item = SolverEvent<N>;
+    type Item = SolverEvent<N, E>;
     type IntoIter = SolverIter<N, E>;
 
     /// Returns an iterator of [`SolverEvent`]s that a solver emits.
@@ -149,68 +907,446 @@ impl<N: Value, E: Value> IntoIterator for Solver<N, E> {
     }
 }
 
+#[cfg(feature = "async")]
+impl<N: Value, E: Value> Solver<N, E> {
+    /// Returns a stream of [`SolverEvent`]s that a solver emits.
+    /// 
+    /// The search still runs on the solver thread;
+    /// the events are forwarded to the stream by another background thread.
+    /// Like the iterator, the stream is empty if the solver is not running.
+    pub fn into_stream(mut self) -> SolverStream<N, E> {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+
+        if let Some(mut events) = self.solver_thread.as_mut().and_then(|t| t.events.take()) {
+            thread::spawn(move || {
+                while let Ok(event) = events.recv() {
+                    if tx.unbounded_send(event).is_err() { break; }
+                }
+            });
+        }
+
+        SolverStream { solver: self, events: rx }
+    }
+}
+
+/// A stream of [`SolverEvent`]s that a solver emits.
+#[cfg(feature = "async")]
+pub struct SolverStream<N: Value, E: Value> {
+    solver: Solver<N, E>,
+    events: futures::channel::mpsc::UnboundedReceiver<SolverThreadEvent>,
+}
+
+#[cfg(feature = "async")]
+impl<N: Value, E: Value> SolverStream<N, E> {
+    /// Returns a reference to the solver, e.g. to pause or abort it while streaming.
+    pub fn solver(&self) -> &Solver<N, E> { &self.solver }
+}
+
+// The fields are never pinned.
+#[cfg(feature = "async")]
+impl<N: Value, E: Value> Unpin for SolverStream<N, E> {}
+
+#[cfg(feature = "async")]
+impl<N: Value, E: Value> futures::Stream for SolverStream<N, E> {
+    type Item = SolverEvent<N, E>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<SolverEvent<N, E>>> {
+        use futures::StreamExt;
+
+        let this = &mut *self;
+        this.events.poll_next_unpin(cx)
+            .map(|event| event.map(|e| this.solver.map_event(e)))
+    }
+}
+
+
+/// Calls a closure for each solution.
+struct FnCallback<F: FnMut(Vec<usize>)>(F);
+
+impl<F: FnMut(Vec<usize>)> Callback<Matrix> for FnCallback<F> {
+    fn on_solution(&mut self, sol: Vec<usize>, _mat: &mut Matrix) {
+        (self.0)(sol);
+    }
+}
+
+/// Pauses the search at the first solution, so that it can be resumed later.
+#[derive(Default)]
+struct StepCallback {
+    solution: Option<Vec<usize>>,
+}
+
+impl Callback<Matrix> for StepCallback {
+    fn on_solution(&mut self, sol: Vec<usize>, mat: &mut Matrix) {
+        self.solution = Some(sol);
+        mat.abort();
+    }
+}
+
+/// Keeps the cheapest solution found so far, and prunes the others.
+#[derive(Default)]
+struct MinimumCostCallback {
+    best: Option<(Vec<usize>, usize)>,
+}
+
+impl Callback<Matrix> for MinimumCostCallback {
+    fn on_solution(&mut self, sol: Vec<usize>, mat: &mut Matrix) {
+        let cost = mat.cost();
+        mat.set_cost_bound(Some(cost));
+        self.best = Some((sol, cost));
+    }
+}
+
+/// Stops the search at the first solution, or when it has backtracked too many times.
+#[cfg(feature = "rand")]
+#[derive(Default)]
+struct RestartCallback {
+    max_backtracks: usize,
+    solution: Option<Vec<usize>>,
+    restarted: bool, // whether the search is aborted to restart
+}
+
+#[cfg(feature = "rand")]
+impl RestartCallback {
+    // The backtrack budget of the first attempt
+    const BASE_BACKTRACKS: usize = 64;
+}
+
+#[cfg(feature = "rand")]
+impl Callback<Matrix> for RestartCallback {
+    fn on_iteration(&mut self, mat: &mut Matrix) {
+        if mat.stats().backtracks > self.max_backtracks {
+            self.restarted = true;
+            mat.abort();
+        }
+    }
+
+    fn on_solution(&mut self, sol: Vec<usize>, mat: &mut Matrix) {
+        self.solution = Some(sol);
+        mat.abort();
+    }
+}
+
+#[derive(Default)]
+struct LexMinCallback {
+    best: Option<Vec<usize>>, // sorted rows
+}
+
+impl LexMinCallback {
+    /// Returns whether the partial solution may be completed to a solution smaller than `best`,
+    /// assuming that any of the remaining rows can be added.
+    fn can_improve(best: &[usize], partial: &[usize], remaining: &[usize]) -> bool {
+        let in_best = |row: &usize| best.binary_search(row).is_ok();
+        // The rows of the best solution that can never be added
+        let missing = best.iter()
+            .find(|&row| !partial.contains(row) && remaining.binary_search(row).is_err())
+            .copied()
+            .unwrap_or(usize::MAX);
+        let limit = missing.min(*best.last().unwrap());
+
+        // Differ from the best first by a row not in it, before any missing row
+        let extra = partial.iter().chain(remaining).filter(|row| !in_best(row)).min();
+        if extra.is_some_and(|&row| row < limit) { return true; }
+        // Or be a proper prefix of the best
+        partial.iter().all(in_best) && partial.iter().max().is_none_or(|&row| row < limit)
+    }
+}
+
+impl Callback<Matrix> for LexMinCallback {
+    fn on_iteration(&mut self, mat: &mut Matrix) {
+        let Some(best) = &self.best else { return };
+        if !LexMinCallback::can_improve(best, mat.partial_solution(), &mat.remaining_rows()) {
+            mat.prune();
+        }
+    }
+
+    fn on_solution(&mut self, mut sol: Vec<usize>, _mat: &mut Matrix) {
+        sol.sort_unstable();
+        if self.best.as_ref().is_none_or(|best| sol < *best) {
+            self.best = Some(sol);
+        }
+    }
+}
+
+/// A search job of a worker, to be run on a thread given to [`Solver::run_on`].
+#[cfg(feature = "threads")]
+pub type SolverJob = Box<dyn FnOnce() + Send + 'static>;
+
+/// The minimum progress to estimate the remaining time from,
+/// since the estimate is unreliable at the beginning of the search.
+#[cfg(feature = "threads")]
+const MIN_ETA_PROGRESS: f32 = 0.01;
+
+/// Estimates the time to make the `remaining` progress,
+/// assuming that it goes at the same rate as the `done` progress made in `elapsed` time.
+#[cfg(feature = "threads")]
+fn estimate_eta(elapsed: Duration, done: f32, remaining: f32) -> Option<Duration> {
+    if done < MIN_ETA_PROGRESS { return None; }
+    Some(elapsed.mul_f32(remaining.max(0.0) / done))
+}
+
+/// Settings of worker threads, given before they start.
+#[cfg(feature = "threads")]
+#[derive(Clone, Copy)]
+struct ThreadSettings {
+    poll_interval: usize,
+    capacity: Option<usize>,
+    search_events: bool,
+    dead_ends: bool,
+}
 
-/// Represents a running thread.
+/// Represents running worker threads.
+#[cfg(feature = "threads")]
 struct SolverThread {
-    tx_signal: Sender<SolverThreadSignal>,
-    rx_event: Receiver<SolverThreadEvent>,
-    _thread: JoinHandle<()>, // TODO: do I need it?
+    tx_signals: Vec<Sender<SolverThreadSignal>>,
+    events: Option<EventReceiver>, // taken when forwarded to a stream
+    stopped: Arc<AtomicUsize>, // number of workers whose search has finished or been aborted
+    abort: Arc<AtomicBool>, // set to abort all workers, checked at every iteration
 }
 
+#[cfg(feature = "threads")]
 impl SolverThread {
     // TODO: terminate thread on drop 
-    fn new(mut mat: Matrix) -> SolverThread {
-        let (tx_signal, rx_signal) = mpsc::channel();
-        let (tx_event, rx_event) = mpsc::channel();
-        
-        let mut callback = ThreadCallback::new(rx_signal, tx_event);
-        let thread = thread::spawn(move || { mat.solve(&mut callback); });
-        
+    fn new(
+        mut mat: Matrix,
+        num_threads: usize,
+        settings: ThreadSettings,
+        spawn: &mut dyn FnMut(SolverJob),
+    ) -> SolverThread {
+        let (tx_event, rx_event) = match settings.capacity {
+            Some(capacity) => {
+                let (tx, rx) = mpsc::sync_channel(capacity);
+                (EventSender::Bounded(tx), rx)
+            }
+            None => {
+                let (tx, rx) = mpsc::channel();
+                (EventSender::Unbounded(tx), rx)
+            }
+        };
+        let mut tx_signals = vec![];
+        let stopped = Arc::new(AtomicUsize::new(0));
+        let abort = Arc::new(AtomicBool::new(false));
+        // A resumed search may have found a solution already
+        let first_found = mat.stats().solutions > 0;
+
+        for index in 0..num_threads {
+            let (tx_signal, rx_signal) = mpsc::channel();
+            let mut mat = match index + 1 < num_threads {
+                true => mat.clone(),
+                false => std::mem::take(&mut mat),
+            };
+            if num_threads > 1 { mat.set_partition(index, num_threads); }
+
+            let mut callback = ThreadCallback::new(rx_signal, tx_event.clone(), abort.clone(), settings);
+            let stopped = stopped.clone();
+            spawn(Box::new(move || {
+                mat.solve(&mut callback);
+                // Count it before the signal receiver in `callback` is dropped
+                stopped.fetch_add(1, Ordering::SeqCst);
+            }));
+            tx_signals.push(tx_signal);
+        }
+
         SolverThread {
-            tx_signal,
-            rx_event,
-            _thread: thread,
+            tx_signals,
+            events: Some(EventReceiver {
+                rx_event,
+                running: num_threads,
+                stats: SearchStats::default(),
+                first_found,
+                pending: None,
+            }),
+            stopped,
+            abort,
+        }
+    }
+
+    /// Sends the signal to all workers that are still running.
+    /// 
+    /// It returns an error if none of them is running.
+    /// Note that the workers that have finished ignore the signal silently.
+    fn send(&self, signal: SolverThreadSignal) -> Result<(), SolverError> {
+        let sent = self.tx_signals.iter()
+            .filter(|tx_signal| tx_signal.send(signal).is_ok())
+            .count();
+
+        if sent > 0 {
+            Ok(())
+        } else if self.stopped.load(Ordering::SeqCst) == self.tx_signals.len() {
+            Err(SolverError::AlreadyFinished)
+        } else {
+            Err(SolverError::ChannelClosed) // some worker has panicked
+        }
+    }
+
+    fn recv(&mut self) -> Result<SolverThreadEvent, RecvError> {
+        self.events.as_mut().ok_or(RecvError)?.recv()
+    }
+}
+
+/// Sends the events of a worker, through a bounded channel if the capacity is limited.
+#[cfg(feature = "threads")]
+#[derive(Clone)]
+enum EventSender {
+    Unbounded(Sender<SolverThreadEvent>),
+    Bounded(SyncSender<SolverThreadEvent>),
+}
+
+#[cfg(feature = "threads")]
+#[allow(clippy::result_large_err)] // the errors give back the unsent event, as the std channels do
+impl EventSender {
+    /// Sends the event, waiting until the channel has room for it.
+    fn send(&self, event: SolverThreadEvent) -> Result<(), SendError<SolverThreadEvent>> {
+        match self {
+            EventSender::Unbounded(tx) => tx.send(event),
+            EventSender::Bounded(tx) => tx.send(event),
         }
     }
 
-    fn send(&self, signal: SolverThreadSignal) -> Result<(), ()> {
-        // TODO: Handle signals after the thread is terminated
-        // e.g. what happens when it gets RequestProgress after thread is finished?
-        self.tx_signal.send(signal).map_err(|_| {()})
+    /// Sends the event only if the channel has room for it.
+    fn try_send(&self, event: SolverThreadEvent) -> Result<(), TrySendError<SolverThreadEvent>> {
+        match self {
+            EventSender::Unbounded(tx) => tx.send(event).map_err(|SendError(e)| TrySendError::Disconnected(e)),
+            EventSender::Bounded(tx) => tx.try_send(event),
+        }
     }
+}
+
+/// Receives the events of all workers.
+#[cfg(feature = "threads")]
+struct EventReceiver {
+    rx_event: Receiver<SolverThreadEvent>,
+    running: usize, // number of workers that have not finished yet
+    stats: SearchStats, // sum of the statistics of finished workers
+    first_found: bool, // whether the first solution has been reported
+    pending: Option<SolverThreadEvent>, // the event to return after the first solution or the final progress
+}
+
+#[cfg(feature = "threads")]
+impl EventReceiver {
+    fn recv(&mut self) -> Result<SolverThreadEvent, RecvError> {
+        if let Some(event) = self.pending.take() { return Ok(event); }
+        loop {
+            let event = self.rx_event.recv()?;
+            // Tracked here rather than in the workers, so that it is emitted only once across them
+            if let SolverThreadEvent::SolutionFound(sol) = &event {
+                if !self.first_found {
+                    self.first_found = true;
+                    let first = SolverThreadEvent::FirstSolutionFound(sol.clone());
+                    self.pending = Some(event);
+                    return Ok(first);
+                }
+            }
+            // Only the last worker reports that the whole search has finished
+            if let SolverThreadEvent::Finished(stats) = event {
+                self.stats.solutions += stats.solutions;
+                self.stats.iterations += stats.iterations;
+                self.stats.backtracks += stats.backtracks;
+                self.stats.max_depth = self.stats.max_depth.max(stats.max_depth);
 
-    fn recv(&self) -> Result<SolverThreadEvent, RecvError> {
-        // TODO: Emit "Finished" event when the DLX algorithm has terminated successfully
-        self.rx_event.recv()
+                self.running -= 1;
+                if self.running > 0 { continue; }
+                // The last requested progress may be far behind, so complete it
+                self.pending = Some(SolverThreadEvent::Finished(self.stats));
+                return Ok(SolverThreadEvent::ProgressUpdated { fraction: 1.0, eta: Some(Duration::ZERO) });
+            }
+            return Ok(event);
+        }
     }
 }
 
+#[cfg(feature = "threads")]
 struct ThreadCallback {
     signal: Receiver<SolverThreadSignal>,
-    event: Sender<SolverThreadEvent>,
+    event: EventSender,
+    abort: Arc<AtomicBool>, // cheaper to check than the signals, so checked at every iteration
+    poll_interval: usize, // check signals every `poll_interval` iterations
+    search_events: bool, // whether to emit branch and backtrack events
+    dead_ends: bool, // whether to emit dead end events
+    iterations: usize, // iterations since the last check
+    started: Option<(Instant, f32)>, // when the search started, and its progress at that time
+    paused: Cell<Duration>, // total time spent while paused
 }
 
+#[cfg(feature = "threads")]
 impl ThreadCallback {
+    // How long to wait for signals between the attempts to send an event to a full channel
+    const FULL_CHANNEL_POLL: Duration = Duration::from_millis(1);
+
     fn new(
         signal: Receiver<SolverThreadSignal>,
-        event: Sender<SolverThreadEvent>,
+        event: EventSender,
+        abort: Arc<AtomicBool>,
+        settings: ThreadSettings,
     ) -> ThreadCallback {
-        ThreadCallback { signal, event }
+        ThreadCallback {
+            signal,
+            event,
+            abort,
+            poll_interval: settings.poll_interval,
+            search_events: settings.search_events,
+            dead_ends: settings.dead_ends,
+            iterations: 0,
+            started: None,
+            paused: Cell::new(Duration::ZERO),
+        }
+    }
+
+    /// Sends an event emitted in the middle of the search.
+    /// 
+    /// While the channel is full, it keeps handling the signals,
+    /// so that a worker waiting for a slow consumer can still be paused or aborted.
+    fn deliver(&self, mut event: SolverThreadEvent, mat: &mut Matrix) {
+        loop {
+            match self.event.try_send(event) {
+                Err(TrySendError::Full(e)) => event = e,
+                _ => return,
+            }
+
+            let signal = match self.signal.recv_timeout(ThreadCallback::FULL_CHANNEL_POLL) {
+                Ok(SolverThreadSignal::Pause) => self.pause(mat),
+                Ok(SolverThreadSignal::RequestProgress) => {
+                    self.update_progress(mat);
+                    continue;
+                }
+                Ok(signal) => signal,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => SolverThreadSignal::Abort,
+            };
+            if let SolverThreadSignal::Abort = signal {
+                // Still deliver the event, since the search resumes after it
+                self.event.send(event).ok();
+                mat.abort();
+                return;
+            }
+        }
     }
 
-    fn update_progress(&self) {
-        // TODO: implement progress update (in dlx)
-        self.event.send(SolverThreadEvent::ProgressUpdated(0.0)).ok();
-        todo!()
+    fn update_progress(&self, mat: &Matrix) {
+        let fraction = mat.progress();
+        let eta = self.started.and_then(|(start, start_fraction)| {
+            let elapsed = start.elapsed().saturating_sub(self.paused.get());
+            estimate_eta(elapsed, fraction - start_fraction, 1.0 - fraction)
+        });
+        self.event.send(SolverThreadEvent::ProgressUpdated { fraction, eta }).ok();
     }
 
     // Returns a signal received while paused.
-    fn pause(&self) -> SolverThreadSignal {
+    // The matrix is left untouched, so the search resumes from the same node.
+    fn pause(&self, mat: &Matrix) -> SolverThreadSignal {
         self.event.send(SolverThreadEvent::Paused).ok();
+        let mut paused_at = Instant::now();
         loop {
-            match self.signal.recv() {
+            let signal = self.signal.recv();
+            // Keep the paused time up to date, so that the ETA excludes it
+            self.paused.set(self.paused.get() + paused_at.elapsed());
+            paused_at = Instant::now();
+
+            match signal {
                 Ok(SolverThreadSignal::Run) => break SolverThreadSignal::Run,
-                Ok(SolverThreadSignal::RequestProgress) => (),
+                Ok(SolverThreadSignal::RequestProgress) => self.update_progress(mat),
                 Ok(SolverThreadSignal::Pause) => (),
                 Ok(SolverThreadSignal::Abort) => break SolverThreadSignal::Abort,
                 Err(RecvError) => break SolverThreadSignal::Abort,
@@ -219,12 +1355,25 @@ impl ThreadCallback {
     }
 }
 
+#[cfg(feature = "threads")]
 impl Callback<Matrix> for ThreadCallback {
-    fn on_solution(&mut self, sol: Vec<usize>, _mat: &mut Matrix) {
-        self.event.send(SolverThreadEvent::SolutionFound(sol)).ok();
+    fn on_solution(&mut self, sol: Vec<usize>, mat: &mut Matrix) {
+        self.deliver(SolverThreadEvent::SolutionFound(sol), mat);
     }
     
     fn on_iteration(&mut self, mat: &mut Matrix) {
+        // A resumed search may have made some progress already
+        self.started.get_or_insert_with(|| (Instant::now(), mat.progress()));
+        // Checked before the poll interval, so that the abort is never delayed.
+        // The abort signal is still sent to wake up the paused workers, which handle it in `pause`.
+        if self.abort.load(Ordering::Relaxed) {
+            mat.abort();
+            return;
+        }
+        self.iterations += 1;
+        if self.iterations < self.poll_interval { return; }
+        self.iterations = 0;
+
         let mut pause_signal = None; // signal received while paused
 
         let abort = loop {
@@ -236,8 +1385,8 @@ impl Callback<Matrix> for ThreadCallback {
 
             match signal {
                 Ok(SolverThreadSignal::Run) => (),
-                Ok(SolverThreadSignal::RequestProgress) => self.update_progress(),
-                Ok(SolverThreadSignal::Pause) => pause_signal = Some(self.pause()),
+                Ok(SolverThreadSignal::RequestProgress) => self.update_progress(mat),
+                Ok(SolverThreadSignal::Pause) => pause_signal = Some(self.pause(mat)),
                 Ok(SolverThreadSignal::Abort) => break true,
                 Err(TryRecvError::Disconnected) => break true,
                 Err(TryRecvError::Empty) => break false,
@@ -247,21 +1396,47 @@ impl Callback<Matrix> for ThreadCallback {
         if abort { mat.abort(); }
     }
 
-    fn on_abort(&mut self, _mat: &mut Matrix) {
-        // TODO: write matrix serialization code
-        // self.event.send(SolverThreadEvent::Aborted(mat.serialize()));
+    fn on_abort(&mut self, mat: &mut Matrix) {
+        // Report where the search has stopped, since it may be resumed later
+        self.update_progress(mat);
+        // The search is over in this thread, so hand over the matrix itself.
+        let mat = std::mem::take(mat);
+        self.event.send(SolverThreadEvent::Aborted(mat)).ok();
     }
 
-    fn on_finish(&mut self) {
-        self.event.send(SolverThreadEvent::Finished).ok();
+    fn on_branch_advanced(&mut self, branch: usize, total: usize, mat: &mut Matrix) {
+        self.deliver(SolverThreadEvent::BranchAdvanced { branch, total }, mat);
     }
-}
 
+    fn on_branch(&mut self, col: usize, row: usize, mat: &mut Matrix) {
+        if !self.search_events { return; }
+        self.deliver(SolverThreadEvent::Branch { column: col - 1, row: row - 1 }, mat);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn on_backtrack(&mut self, mat: &mut Matrix) {
+        if !self.search_events { return; }
+        self.deliver(SolverThreadEvent::Backtrack, mat);
+    }
 
+    fn on_dead_end(&mut self, _col: usize, mat: &mut Matrix) {
+        if !self.dead_ends { return; }
+        self.deliver(SolverThreadEvent::DeadEnd(mat.uncovered_columns()), mat);
+    }
+
+    fn on_finish(&mut self, mat: &mut Matrix) {
+        // The final progress is reported once all workers have finished (see `EventReceiver`)
+        self.event.send(SolverThreadEvent::Finished(mat.stats())).ok();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "threads")]
+    use crate::problems::polyomino::{Board, CompoundConstraint, CompoundName, Polyomino, PolyominoPacking};
+
+    #[cfg(feature = "threads")]
     #[test]
     fn solver_can_solve_problem() {
         let mut prob = Problem::default();
@@ -275,7 +1450,7 @@ mod tests {
 
         let mut solver = Solver::new(prob);
         let mut solutions = vec![];
-        solver.run();
+        solver.run().unwrap();
 
         for event in solver {
             if let SolverEvent::SolutionFound(sol) = event {
@@ -286,6 +1461,383 @@ mod tests {
         assert_eq!(solutions.len(), 4);
     }
 
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_can_iterate_solutions() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let solver = Solver::new(prob.clone());
+        assert_eq!(solver.solutions().unwrap().count(), 4);
+        let solutions: Vec<_> = Solver::new(prob.clone()).solutions().unwrap().collect();
+        assert_eq!(solutions, Solver::new(prob).solve_all().unwrap());
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_can_solve_all() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        assert_eq!(
+            Solver::new(prob).solve_all().unwrap(),
+            vec![vec!["A"], vec!["B", "C", "D"], vec!["B", "F"], vec!["E", "D"]],
+        );
+    }
+
+    #[test]
+    fn solution_order_is_stable() {
+        // The ordering documented in `Problem`. Update it deliberately if a heuristic changes it.
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let expected = vec![vec!["A"], vec!["B", "C", "D"], vec!["B", "F"], vec!["E", "D"]];
+        for _ in 0..3 {
+            assert_eq!(Solver::new(prob.clone()).solve_blocking().unwrap(), expected);
+        }
+        #[cfg(feature = "threads")]
+        assert_eq!(Solver::new(prob).solve_all().unwrap(), expected);
+    }
+
+    #[test]
+    fn min_solution_is_lexicographically_smallest() {
+        let lex_min = |prob: &Problem<&'static str, i32>| {
+            let indices = |sol: &Vec<&str>| {
+                let mut idx: Vec<_> = sol.iter().map(|name| prob.subsets().get_index_of(name).unwrap()).collect();
+                idx.sort_unstable();
+                idx
+            };
+            let all = Solver::new(prob.clone()).solve_blocking().unwrap();
+            let min = Solver::new(prob.clone()).min_solution().unwrap();
+            assert_eq!(min.as_ref().map(indices), all.iter().map(indices).min());
+            min
+        };
+
+        // The first solution found by MRV is not the smallest one
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("F", vec![2, 3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("A", vec![1, 2, 3]);
+        assert_eq!(Solver::new(prob.clone()).solve_blocking().unwrap()[0], vec!["E", "D"]);
+        assert_eq!(lex_min(&prob), Some(vec!["F", "B"]));
+
+        // A solution may contain another one, which is smaller
+        let mut prob = Problem::default();
+        prob.add_constraint(1, 1, 2);
+        prob.add_subset("A", vec![1]);
+        prob.add_subset("B", vec![1]);
+        assert_eq!(lex_min(&prob), Some(vec!["A"]));
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_subset("A", vec![1]);
+        assert_eq!(lex_min(&prob), None);
+    }
+
+    #[test]
+    fn solver_events_can_be_formatted() {
+        let events: [SolverEvent<&str, i32>; 10] = [
+            SolverEvent::SolutionFound(vec!["A", "B"]),
+            SolverEvent::SolutionIndices(vec![0, 1]),
+            SolverEvent::ProgressUpdated { fraction: 0.5, eta: Some(Duration::from_secs(3)) },
+            SolverEvent::BranchAdvanced { branch: 1, total: 3 },
+            SolverEvent::Branch { column: 0, row: 2 },
+            SolverEvent::Backtrack,
+            SolverEvent::DeadEnd(vec![3]),
+            SolverEvent::Paused,
+            SolverEvent::Aborted(Matrix::with_rows(3, &[&[1, 2], &[3]]).unwrap()),
+            SolverEvent::Finished { solutions: 1, iterations: 4, backtracks: 2, max_depth: 2 },
+        ];
+        let formatted: Vec<_> = events.iter().map(|e| format!("{:?}", e.clone())).collect();
+        assert_eq!(formatted, [
+            r#"SolutionFound(["A", "B"])"#,
+            "SolutionIndices([0, 1])",
+            "ProgressUpdated { fraction: 0.5, eta: Some(3s) }",
+            "BranchAdvanced { branch: 1, total: 3 }",
+            "Branch { column: 0, row: 2 }",
+            "Backtrack",
+            "DeadEnd([3])",
+            "Paused",
+            "Aborted(Matrix { rows: 2, cols: 3 })",
+            "Finished { solutions: 1, iterations: 4, backtracks: 2, max_depth: 2 }",
+        ]);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn eta_shrinks_as_progress_grows() {
+        let secs = |s| Duration::from_secs(s);
+        assert_eq!(estimate_eta(secs(10), 0.0, 1.0), None);
+        assert_eq!(estimate_eta(secs(10), MIN_ETA_PROGRESS / 2.0, 0.9), None);
+        let early = estimate_eta(secs(10), 0.25, 0.75).unwrap();
+        let late = estimate_eta(secs(20), 0.5, 0.5).unwrap();
+        assert!(late < early);
+        assert_eq!(estimate_eta(secs(20), 1.0, 0.0), Some(Duration::ZERO));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn progress_updates_have_eta() {
+        // Pentominoes in a 3 x 20 rectangle
+        let mut packing = PolyominoPacking::rectangle(20, 3);
+        for (name, piece) in PolyominoPacking::standard_pentominoes() {
+            packing.add_piece(name, piece);
+        }
+        let mut solver = Solver::new(packing.generate_problem());
+        solver.run().unwrap();
+
+        let mut events = solver.into_iter();
+        let mut progress = vec![];
+        while let Some(event) = events.next() {
+            match event {
+                SolverEvent::BranchAdvanced { .. } => { events.solver().request_progress().ok(); }
+                SolverEvent::ProgressUpdated { fraction, eta } => progress.push((fraction, eta)),
+                _ => (),
+            }
+        }
+
+        assert!(!progress.is_empty());
+        assert!(progress.windows(2).all(|w| w[0].0 <= w[1].0));
+        // The ETA is present once enough progress has been made, but it is not monotonic,
+        // since it depends on the timing of the run
+        assert!(progress.iter().all(|&(fraction, eta)| (0.0..=1.0).contains(&fraction)
+            && (eta.is_some() || fraction < MIN_ETA_PROGRESS)));
+        assert_eq!(progress.last(), Some(&(1.0, Some(Duration::ZERO))));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_can_emit_solution_indices() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let mut solver = Solver::new(prob.clone());
+        solver.emit_indices(true);
+        solver.run().unwrap();
+        let mut events = solver.into_iter();
+        let mut indices = vec![];
+        for event in events.by_ref() {
+            match event {
+                SolverEvent::SolutionIndices(sol) => indices.push(sol),
+                SolverEvent::SolutionFound(_) => panic!("Names should not be emitted"),
+                _ => (),
+            }
+        }
+        assert_eq!(indices, vec![vec![0], vec![1, 2, 3], vec![1, 5], vec![4, 3]]);
+
+        let decoded: Vec<_> = indices.iter().map(|sol| events.solver().decode(sol)).collect();
+        assert_eq!(decoded, Solver::new(prob).solve_all().unwrap());
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_can_emit_search_events() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let count_events = |enabled: bool| {
+            let mut solver = Solver::new(prob.clone());
+            solver.emit_search_events(enabled);
+            solver.run().unwrap();
+
+            let (mut branches, mut backtracks, mut depth) = (vec![], 0, 0);
+            for event in solver {
+                match event {
+                    SolverEvent::Branch { column, row } => {
+                        branches.push((column, row));
+                        depth += 1;
+                    }
+                    SolverEvent::Backtrack => {
+                        assert!(depth > 0, "Backtracked without a branch");
+                        backtracks += 1;
+                        depth -= 1;
+                    }
+                    _ => (),
+                }
+            }
+            (branches, backtracks)
+        };
+
+        assert_eq!(count_events(false), (vec![], 0));
+        let (branches, backtracks) = count_events(true);
+        assert_eq!(branches.len(), backtracks);
+        assert_eq!(branches[0], (0, 0)); // A covers the first constraint
+        assert!(branches.iter().all(|&(column, row)| prob.subsets()[row].contains(&(column as i32 + 1))));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn dead_ends_report_uncovered_elements() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![2, 3]);
+
+        let mut solver = Solver::new(prob);
+        solver.emit_dead_ends(true);
+        solver.run().unwrap();
+        let dead_ends: Vec<_> = solver.into_iter()
+            .filter_map(|event| match event {
+                SolverEvent::DeadEnd(elems) => Some(elems),
+                SolverEvent::SolutionFound(_) => panic!("The problem should be infeasible"),
+                _ => None,
+            })
+            .collect();
+        // A is the only subset covering 1, and it conflicts with B, the only one covering 3
+        assert_eq!(dead_ends, vec![vec![3]]);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn dead_ends_report_uncoverable_elements() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_cover_constraint(4, 2);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![2, 4]);
+        assert_eq!(prob.uncoverable_constraints(), vec![3, 4]);
+
+        let mut solver = Solver::new(prob);
+        solver.emit_dead_ends(true);
+        solver.run().unwrap();
+        let dead_ends: Vec<_> = solver.into_iter()
+            .filter_map(|event| match event {
+                SolverEvent::DeadEnd(elems) => Some(elems),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(dead_ends, vec![vec![3, 4]]);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solvers_can_share_thread_pool() {
+        use std::sync::Mutex;
+
+        // A pool of two reusable workers
+        let (pool, jobs) = mpsc::channel::<SolverJob>();
+        let jobs = Arc::new(Mutex::new(jobs));
+        let workers: Vec<_> = (0..2).map(|_| {
+            let jobs = jobs.clone();
+            thread::spawn(move || loop {
+                let job = jobs.lock().unwrap().recv();
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => break,
+                }
+            })
+        }).collect();
+
+        // Tilings of a 1 x n strip with monominoes and dominoes, counted by Fibonacci numbers
+        let strip = |n: i32| {
+            let mut prob = Problem::default();
+            prob.add_exact_constraints(1..=n);
+            for i in 1..=n { prob.add_subset((i, 1), vec![i]); }
+            for i in 1..n { prob.add_subset((i, 2), vec![i, i + 1]); }
+            prob
+        };
+
+        let solvers: Vec<_> = (1..=10).map(|n| {
+            let mut solver = Solver::new(strip(n));
+            solver.run_on(|job| pool.send(job).unwrap()).unwrap();
+            solver
+        }).collect();
+
+        let counts: Vec<_> = solvers.into_iter()
+            .map(|solver| solver.into_iter().filter(|e| matches!(e, SolverEvent::SolutionFound(_))).count())
+            .collect();
+        assert_eq!(counts, vec![1, 2, 3, 5, 8, 13, 21, 34, 55, 89]);
+
+        drop(pool);
+        for worker in workers { worker.join().unwrap(); }
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_can_find_first_solution() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        assert_eq!(Solver::new(prob.clone()).first_solution().unwrap(), Some(vec!["A"]));
+
+        prob.add_subset("G", vec![4]);
+        assert_eq!(
+            Solver::new(prob).first_solution(),
+            Err(SolverError::InvalidProblem(ProblemError::UnknownElement { subset: 6, index: 0 })),
+        );
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_subset("A", vec![1]);
+        assert_eq!(Solver::new(prob).first_solution().unwrap(), None);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_reports_search_statistics() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let mut solver = Solver::new(prob);
+        solver.run().unwrap();
+
+        let stats = solver.into_iter().find_map(|event| match event {
+            SolverEvent::Finished { solutions, iterations, max_depth, .. } => Some((solutions, iterations, max_depth)),
+            _ => None,
+        });
+        let (solutions, iterations, max_depth) = stats.unwrap();
+        assert_eq!(solutions, 4);
+        assert!(iterations > 0);
+        assert!(max_depth <= 3);
+    }
+
+    #[cfg(feature = "threads")]
     #[test]
     fn solver_can_solve_problem_with_multiplicity() {
         let mut prob = Problem::default();
@@ -296,7 +1848,7 @@ mod tests {
 
         let mut solver = Solver::new(prob);
         let mut solutions = vec![];
-        solver.run();
+        solver.run().unwrap();
         
         for event in solver {
             if let SolverEvent::SolutionFound(sol) = event {
@@ -306,4 +1858,1001 @@ mod tests {
         
         assert_eq!(solutions.len(), 4);
     }
+
+    #[test]
+    fn solver_can_solve_blocking() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let expected = vec![vec!["A"], vec!["B", "C", "D"], vec!["B", "F"], vec!["E", "D"]];
+        assert_eq!(Solver::new(prob.clone()).solve_blocking().unwrap(), expected);
+
+        let mut solutions = vec![];
+        Solver::new(prob).solve_blocking_with(|sol| solutions.push(sol)).unwrap();
+        assert_eq!(solutions, expected);
+    }
+
+    #[test]
+    fn solver_can_iterate_lazily() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let mut solver = Solver::new(prob);
+        let mut iter = solver.iter_blocking().unwrap();
+        let first: Vec<_> = iter.by_ref().take(2).collect();
+        assert_eq!(first, vec![vec!["A"], vec!["B", "C", "D"]]);
+
+        // The search has stopped right after the second solution
+        let stats = iter.matrix.as_ref().unwrap().stats();
+        assert_eq!(stats.solutions, 2);
+
+        let rest: Vec<_> = iter.by_ref().collect();
+        assert_eq!(rest, vec![vec!["B", "F"], vec!["E", "D"]]);
+        assert!(iter.matrix.is_none());
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn solver_can_force_subsets() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let mut forced_a = prob.clone();
+        forced_a.force_subset("A");
+        assert_eq!(Solver::new(forced_a).solve_blocking().unwrap(), vec![vec!["A"]]);
+
+        let mut forced_d = prob.clone();
+        forced_d.force_subset("D");
+        assert_eq!(Solver::new(forced_d).solve_blocking().unwrap(), vec![vec!["D", "B", "C"], vec!["D", "E"]]);
+
+        prob.force_subset("B");
+        prob.force_subset("E");
+        assert_eq!(Solver::new(prob).solve_blocking(), Err(ProblemError::ConflictingSubset { subset: 4 }));
+    }
+
+    #[test]
+    fn solver_can_limit_depth() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let solve = |max_depth: usize| {
+            let mut solver = Solver::new(prob.clone());
+            solver.set_max_depth(max_depth);
+            solver.solve_blocking().unwrap()
+        };
+
+        assert_eq!(solve(0), Vec::<Vec<&str>>::new());
+        assert_eq!(solve(1), vec![vec!["A"]]);
+        assert_eq!(solve(2), vec![vec!["A"], vec!["B", "F"], vec!["E", "D"]]);
+        assert_eq!(solve(3).len(), 4);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn solver_can_shuffle_with_seed() {
+        use crate::problems::latin_square::LatinSquare;
+
+        let prob = LatinSquare::from_grid(vec![vec![0; 4]; 4]).generate_problem();
+        let solve = |seed: u64| {
+            let mut solutions = Solver::new(prob.clone()).with_seed(seed).solve_blocking().unwrap();
+            for sol in &mut solutions { sol.sort_unstable(); }
+            solutions
+        };
+        let sorted = |mut solutions: Vec<Vec<_>>| {
+            solutions.sort_unstable();
+            solutions
+        };
+
+        let (first, second) = (solve(1), solve(2));
+        assert_eq!(first.len(), 576);
+        assert_ne!(first, second);
+        assert_eq!(sorted(first.clone()), sorted(second));
+        assert_eq!(solve(1), first);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn randomized_restarts_find_a_solution() {
+        use crate::problems::polyomino::PolyominoPacking;
+
+        let mut packing = PolyominoPacking::rectangle(10, 6);
+        for (name, piece) in PolyominoPacking::standard_pentominoes() {
+            packing.add_piece(name, piece);
+        }
+        let prob = packing.generate_problem();
+
+        for seed in 0..4 {
+            let sol = Solver::new(prob.clone()).find_one_randomized(seed, 16).unwrap().unwrap();
+            assert!(prob.is_exact_cover(&sol));
+        }
+        let find = |seed| Solver::new(prob.clone()).find_one_randomized(seed, 16).unwrap();
+        assert_eq!(find(7), find(7));
+
+        // The search is exhausted without restarting
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![2, 3]);
+        assert_eq!(Solver::new(prob).find_one_randomized(0, usize::MAX), Ok(None));
+    }
+
+    #[cfg(all(feature = "threads", feature = "rand"))]
+    #[test]
+    fn solutions_can_be_compared_as_sets() {
+        use crate::problems::latin_square::LatinSquare;
+
+        let prob = LatinSquare::from_grid(vec![
+            vec![1, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 3, 0],
+            vec![0, 0, 0, 0],
+        ]).generate_problem();
+        let solve = |seed: u64| Solver::new(prob.clone()).with_seed(seed).solve_all().unwrap();
+        let solve_as_sets = |seed: u64| Solver::new(prob.clone()).with_seed(seed).solve_all_as_sets().unwrap();
+
+        assert_ne!(solve(1), solve(2));
+        let (first, second) = (solve_as_sets(1), solve_as_sets(2));
+        assert_eq!(first.len(), solve(1).len());
+        assert!(first.iter().all(|sol| sol.len() == 16));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn solver_finds_solutions_in_deterministic_order() {
+        use crate::problems::latin_square::LatinSquare;
+
+        let solve = || {
+            let prob = LatinSquare::from_grid(vec![vec![0; 4]; 4]).generate_problem();
+            Solver::new(prob).solve_blocking().unwrap()
+        };
+
+        let solutions = solve();
+        assert_eq!(solutions.len(), 576);
+        assert_eq!(solutions, solve());
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_emits_branch_advanced_events() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let mut solver = Solver::new(prob);
+        let mut branches = vec![];
+        solver.run().unwrap();
+
+        for event in solver {
+            if let SolverEvent::BranchAdvanced { branch, total } = event {
+                branches.push((branch, total));
+            }
+        }
+
+        // The first chosen column is 1, which is covered by "A", "B" and "E".
+        assert_eq!(branches, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_can_run_in_parallel() {
+        use crate::problems::latin_square::LatinSquare;
+
+        let prob = LatinSquare::from_grid(vec![vec![0; 4]; 4]).generate_problem();
+        let solve = |num_threads: Option<usize>| {
+            let mut solver = Solver::new(prob.clone());
+            match num_threads {
+                Some(n) => solver.run_parallel(n).unwrap(),
+                None => solver.run().unwrap(),
+            }
+
+            let mut solutions = vec![];
+            let mut finished = 0;
+            for event in solver {
+                match event {
+                    SolverEvent::SolutionFound(mut sol) => {
+                        sol.sort_unstable();
+                        solutions.push(sol);
+                    }
+                    SolverEvent::Finished { solutions: count, .. } => {
+                        assert_eq!(count, 576);
+                        finished += 1;
+                    }
+                    _ => (),
+                }
+            }
+            solutions.sort_unstable();
+            assert_eq!(finished, 1);
+            solutions
+        };
+
+        let expected = solve(None);
+        assert_eq!(expected.len(), 576);
+        assert_eq!(solve(Some(3)), expected);
+        assert_eq!(solve(Some(1)), expected);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn poll_interval_does_not_change_solutions() {
+        use crate::problems::latin_square::LatinSquare;
+
+        let prob = LatinSquare::from_grid(vec![vec![0; 4]; 4]).generate_problem();
+        let solve = |interval: usize| {
+            let mut solver = Solver::new(prob.clone());
+            solver.set_poll_interval(interval);
+            solver.run().unwrap();
+
+            solver.into_iter()
+                .filter_map(|event| match event {
+                    SolverEvent::SolutionFound(sol) => Some(sol),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let expected = solve(1);
+        assert_eq!(expected.len(), 576);
+        assert_eq!(solve(0), expected);
+        assert_eq!(solve(7), expected);
+        assert_eq!(solve(1_000_000), expected);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn first_solution_is_reported_once() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        for num_threads in [1, 3] {
+            let mut solver = Solver::new(prob.clone());
+            solver.run_parallel(num_threads).unwrap();
+            let events: Vec<_> = solver.into_iter().collect();
+
+            let firsts: Vec<_> = events.iter()
+                .enumerate()
+                .filter_map(|(i, event)| match event {
+                    SolverEvent::FirstSolutionFound(sol) => Some((i, sol)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(firsts.len(), 1);
+            let (i, first) = firsts[0];
+            // It precedes all the solutions, including its own
+            let solutions: Vec<_> = events.iter()
+                .enumerate()
+                .filter_map(|(j, event)| match event {
+                    SolverEvent::SolutionFound(sol) => Some((j, sol)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(solutions.len(), 4);
+            assert_eq!(solutions[0], (i + 1, first));
+        }
+
+        // Not reported again after resuming
+        let mut solver = Solver::new(prob.clone());
+        solver.run().unwrap();
+        let mut iter = solver.into_iter();
+        let mut matrix = None;
+        while let Some(event) = iter.next() {
+            match event {
+                SolverEvent::SolutionFound(_) => { iter.solver().abort().ok(); }
+                SolverEvent::Aborted(mat) => matrix = Some(mat),
+                _ => (),
+            }
+        }
+        let mut solver = Solver::resume(prob.clone(), matrix.unwrap());
+        solver.run().unwrap();
+        assert!(!solver.into_iter().any(|event| matches!(event, SolverEvent::FirstSolutionFound(_))));
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_subset("A", vec![1]);
+        let mut solver = Solver::new(prob);
+        solver.run().unwrap();
+        assert!(!solver.into_iter().any(|event| matches!(event, SolverEvent::FirstSolutionFound(_))));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn bounded_channel_throttles_solver() {
+        use crate::problems::latin_square::LatinSquare;
+
+        let prob = LatinSquare::from_grid(vec![vec![0; 4]; 4]).generate_problem();
+        let capacity = 2;
+
+        // A slow consumer receives all the solutions in order
+        let mut solver = Solver::new(prob.clone());
+        solver.set_channel_capacity(capacity);
+        solver.run().unwrap();
+        let mut found = 0;
+        for event in solver {
+            if let SolverEvent::SolutionFound(_) = event { found += 1; }
+            if found == 3 { thread::sleep(Duration::from_millis(50)); }
+        }
+        assert_eq!(found, 576);
+
+        // The worker waits for the consumer, so only a few events are sent until the abort
+        let mut solver = Solver::new(prob.clone());
+        solver.set_channel_capacity(capacity);
+        solver.run().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        solver.pause().unwrap();
+        solver.abort().unwrap();
+
+        let mut events: Vec<_> = solver.into_iter().collect();
+        let Some(SolverEvent::Aborted(matrix)) = events.pop() else { panic!("The solver is not aborted") };
+        // The buffered events, the event being sent, `Paused`, the progress at the abort,
+        // and `FirstSolutionFound` which is not sent through the channel
+        assert!(events.len() <= capacity + 4);
+
+        // No solution is lost while the worker is waiting
+        let found = events.iter().filter(|event| matches!(event, SolverEvent::SolutionFound(_))).count();
+        let rest = Solver::resume(prob, matrix).solve_blocking().unwrap();
+        assert_eq!(found + rest.len(), 576);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn progress_is_reported_while_channel_is_full() {
+        use crate::problems::latin_square::LatinSquare;
+
+        let prob = LatinSquare::from_grid(vec![vec![0; 4]; 4]).generate_problem();
+        let mut solver = Solver::new(prob);
+        solver.set_channel_capacity(1);
+        solver.run().unwrap();
+        // The worker is waiting for the consumer
+        thread::sleep(Duration::from_millis(50));
+        solver.request_progress().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        solver.abort().unwrap();
+
+        let progress: Vec<_> = solver.into_iter()
+            .filter_map(|event| match event {
+                SolverEvent::ProgressUpdated { fraction, .. } => Some(fraction),
+                _ => None,
+            })
+            .collect();
+        // The requested progress, and the progress at the abort
+        assert_eq!(progress.len(), 2);
+        assert!(progress[0] < 1.0);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn solver_can_stream_events() {
+        use futures::StreamExt;
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let mut solver = Solver::new(prob);
+        solver.run().unwrap();
+
+        let mut stream = solver.into_stream();
+        let mut solutions = vec![];
+        while let Some(event) = stream.next().await {
+            if let SolverEvent::SolutionFound(sol) = event {
+                solutions.push(sol);
+            }
+        }
+
+        assert_eq!(solutions, vec![vec!["A"], vec!["B", "C", "D"], vec!["B", "F"], vec!["E", "D"]]);
+    }
+
+    #[test]
+    fn solver_can_solve_into_trie() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let trie = Solver::new(prob).solve_into_trie().unwrap();
+
+        assert_eq!(trie.len(), 4);
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            vec![vec!["A"], vec!["B", "C", "D"], vec!["B", "F"], vec!["E", "D"]],
+        );
+
+        // A solution comes before its extensions, unlike the order they are found
+        let mut prob = Problem::default();
+        prob.add_exact_constraint("x");
+        prob.add_optional_constraint("y");
+        prob.add_subset("A", vec!["x"]);
+        prob.add_subset("B", vec!["y"]);
+
+        assert_eq!(Solver::new(prob.clone()).solve_blocking().unwrap(), vec![vec!["A", "B"], vec!["A"]]);
+        assert_eq!(
+            Solver::new(prob).solve_into_trie().unwrap().iter().collect::<Vec<_>>(),
+            vec![vec!["A"], vec!["A", "B"]],
+        );
+    }
+
+    #[test]
+    fn solver_can_find_minimum_cost_solution() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=4);
+        prob.add_subset("A", vec![1]);
+        prob.add_subset("B", vec![2]);
+        prob.add_subset("C", vec![3]);
+        prob.add_subset("D", vec![4]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![3, 4]);
+        prob.add_subset("G", vec![2, 3]);
+
+        // Minimum number of subsets
+        let (mut sol, cost) = Solver::new(prob.clone()).solve_minimum().unwrap().unwrap();
+        sol.sort_unstable();
+        assert_eq!((sol, cost), (vec!["E", "F"], 2));
+
+        // Minimum total weight
+        prob.add_weighted_subset("E", vec![1, 2], 5);
+        prob.add_weighted_subset("G", vec![2, 3], 2);
+        let (mut sol, cost) = Solver::new(prob.clone()).solve_minimum().unwrap().unwrap();
+        sol.sort_unstable();
+        assert_eq!((sol, cost), (vec!["A", "B", "F"], 3));
+
+        prob.remove_subset(&"A");
+        prob.remove_subset(&"E");
+        assert_eq!(Solver::new(prob).solve_minimum().unwrap(), None);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_cannot_be_controlled_unless_running() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+
+        let mut solver = Solver::new(prob);
+        assert_eq!(solver.pause(), Err(SolverError::NotRunning));
+        assert_eq!(solver.abort(), Err(SolverError::NotRunning));
+
+        solver.run().unwrap();
+        let mut iter = solver.into_iter();
+        iter.by_ref().for_each(drop);
+
+        // All events are received, so the thread has finished.
+        assert_eq!(iter.solver().pause(), Err(SolverError::AlreadyFinished));
+        assert_eq!(iter.solver().abort(), Err(SolverError::AlreadyFinished));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn finished_solver_rejects_signals() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_subset("A", vec![1, 2]);
+
+        for _ in 0..20 {
+            let mut solver = Solver::new(prob.clone());
+            solver.run().unwrap();
+            let mut iter = solver.into_iter();
+            assert!(iter.any(|event| matches!(event, SolverEvent::Finished { .. })));
+
+            // Right after the last event, before the worker may have hung up
+            assert_eq!(iter.solver().request_progress(), Err(SolverError::AlreadyFinished));
+            assert_eq!(iter.solver().pause(), Err(SolverError::AlreadyFinished));
+            assert_eq!(iter.solver().abort(), Err(SolverError::AlreadyFinished));
+            assert_eq!(iter.solver_mut().run(), Err(SolverError::AlreadyFinished));
+            assert_eq!(iter.solver().status(), SolverStatus::Finished);
+            assert!(iter.next().is_none());
+        }
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn progress_is_reported_when_search_stops() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        // The progress reaches 1 right before the search finishes
+        for num_threads in [1, 2] {
+            let mut solver = Solver::new(prob.clone());
+            solver.run_parallel(num_threads).unwrap();
+            let events: Vec<_> = solver.into_iter().collect();
+            let [.., progress, finished] = &events[..] else { panic!("Too few events") };
+            assert!(matches!(finished, SolverEvent::Finished { .. }));
+            assert!(matches!(progress, SolverEvent::ProgressUpdated { fraction, .. } if *fraction == 1.0));
+        }
+
+        // An aborted search reports how far it has got instead
+        let mut solver = Solver::new(pentomino_problem());
+        solver.run().unwrap();
+        let mut iter = solver.into_iter();
+        let mut events = vec![];
+        while let Some(event) = iter.next() {
+            if let SolverEvent::SolutionFound(_) = event { iter.solver().abort().ok(); }
+            events.push(event);
+        }
+        let [.., progress, aborted] = &events[..] else { panic!("Too few events") };
+        assert!(matches!(aborted, SolverEvent::Aborted(_)));
+        assert!(matches!(progress, SolverEvent::ProgressUpdated { fraction, .. } if 0.0 < *fraction && *fraction < 1.0));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn progress_is_completed_after_all_workers_finish() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        for num_threads in [2, 3, 4] {
+            let mut solver = Solver::new(prob.clone());
+            solver.run_parallel(num_threads).unwrap();
+            let events: Vec<_> = solver.into_iter().collect();
+            let completed: Vec<_> = events.iter().enumerate()
+                .filter(|(_, e)| matches!(e, SolverEvent::ProgressUpdated { fraction, .. } if *fraction == 1.0))
+                .map(|(i, _)| i)
+                .collect();
+            assert_eq!(completed, vec![events.len() - 2]);
+            assert!(matches!(events.last(), Some(SolverEvent::Finished { .. })));
+        }
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_reports_its_status() {
+        let mut solver = Solver::new(pentomino_problem());
+        assert_eq!(solver.status(), SolverStatus::Idle);
+
+        solver.run().unwrap();
+        assert_eq!(solver.status(), SolverStatus::Running);
+        solver.pause().unwrap();
+        assert_eq!(solver.status(), SolverStatus::Paused);
+        solver.run().unwrap();
+        assert_eq!(solver.status(), SolverStatus::Running);
+        solver.pause().unwrap();
+        assert_eq!(solver.status(), SolverStatus::Paused);
+        solver.abort().unwrap();
+
+        let mut iter = solver.into_iter();
+        assert!(iter.by_ref().any(|event| matches!(event, SolverEvent::Aborted(_))));
+        assert_eq!(iter.solver().status(), SolverStatus::Aborted);
+
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_subset("A", vec![1, 2]);
+        let mut solver = Solver::new(prob);
+        solver.run().unwrap();
+
+        let mut iter = solver.into_iter();
+        iter.by_ref().for_each(drop);
+        assert_eq!(iter.solver().status(), SolverStatus::Finished);
+    }
+
+    #[test]
+    fn subsets_can_be_added_incrementally() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![3]);
+
+        let mut solver = Solver::new(prob);
+        let solutions = |solver: &mut Solver<_, _>| solver.iter_blocking().unwrap().collect::<Vec<_>>();
+        assert_eq!(solutions(&mut solver), vec![vec!["A", "B"]]);
+
+        solver.add_subset_and_reset("C", vec![1]);
+        solver.add_subset_and_reset("D", vec![2]);
+        assert_eq!(solver.matrix.as_ref().map(Matrix::row_count), Some(4));
+        let expected = Solver::new(solver.problem.clone()).solve_blocking().unwrap();
+        assert_eq!(solutions(&mut solver), expected);
+        assert_eq!(expected, vec![vec!["B", "A"], vec!["B", "C", "D"]]);
+
+        // The matrix is generated from scratch when an existing subset is replaced
+        solver.add_subset_and_reset("A", vec![1, 2, 3]);
+        assert!(solver.matrix.is_none());
+        let expected = Solver::new(solver.problem.clone()).solve_blocking().unwrap();
+        assert_eq!(solutions(&mut solver), expected);
+        assert_eq!(expected.len(), 2);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_can_run_again_after_reset() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let count_solutions = |iter: &mut SolverIter<_, _>| {
+            iter.filter(|event| matches!(event, SolverEvent::SolutionFound(_))).count()
+        };
+
+        let mut solver = Solver::new(prob);
+        solver.run().unwrap();
+        let mut iter = solver.into_iter();
+        assert_eq!(count_solutions(&mut iter), 4);
+
+        let mut solver = iter.into_solver();
+        assert_eq!(solver.run(), Err(SolverError::AlreadyFinished));
+        solver.reset();
+        assert_eq!(solver.status(), SolverStatus::Idle);
+        assert_eq!(solver.pause(), Err(SolverError::NotRunning));
+
+        solver.run().unwrap();
+        assert_eq!(count_solutions(&mut solver.into_iter()), 4);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_reports_infeasible_problem() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=4);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![2, 3]);
+
+        let mut solver = Solver::new(prob.clone());
+        solver.run().unwrap();
+        let finished = solver.into_iter().find_map(|event| match event {
+            SolverEvent::Finished { solutions, iterations, .. } => Some((solutions, iterations)),
+            _ => None,
+        });
+        assert_eq!(finished, Some((0, 1)));
+
+        assert_eq!(Solver::new(prob.clone()).solve_blocking().unwrap(), Vec::<Vec<&str>>::new());
+        assert_eq!(Solver::new(prob).solve_minimum().unwrap(), None);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_rejects_invalid_problem() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=2);
+        prob.add_subset("A", vec![1, 2]);
+        prob.add_subset("B", vec![1, 3]);
+
+        let mut solver = Solver::new(prob);
+        assert_eq!(solver.run(), Err(SolverError::InvalidProblem(ProblemError::UnknownElement { subset: 1, index: 1 })));
+        assert_eq!(solver.into_iter().count(), 0);
+    }
+
+    #[test]
+    fn solver_can_step_through_search() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let mut solver = Solver::new(prob.clone());
+        let mut steps = vec![];
+        while let Some(step) = solver.step().unwrap() {
+            steps.push(step);
+            assert!(steps.len() < 100, "The search should terminate");
+        }
+        assert_eq!(solver.step(), Ok(None));
+
+        assert_eq!(steps[..4], [
+            StepEvent::ConstraintChosen(1),
+            StepEvent::SubsetSelected("A"),
+            StepEvent::SolutionFound(vec!["A"]),
+            StepEvent::Backtracked("A"),
+        ]);
+
+        // Subsets are selected and backtracked in the stack order
+        let mut partial_sol = vec![];
+        for step in &steps {
+            match step {
+                StepEvent::SubsetSelected(name) => partial_sol.push(*name),
+                StepEvent::Backtracked(name) => assert_eq!(partial_sol.pop(), Some(*name)),
+                StepEvent::SolutionFound(sol) => assert_eq!(sol, &partial_sol),
+                StepEvent::ConstraintChosen(_) => (),
+            }
+        }
+        assert!(partial_sol.is_empty());
+
+        let solutions: Vec<_> = steps.into_iter()
+            .filter_map(|step| match step {
+                StepEvent::SolutionFound(sol) => Some(sol),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(solutions, Solver::new(prob).solve_blocking().unwrap());
+    }
+
+    #[test]
+    fn solver_can_verify_candidate() {
+        let mut prob = Problem::default();
+        prob.add_exact_constraints(1..=3);
+        prob.add_subset("A", vec![1, 2, 3]);
+        prob.add_subset("B", vec![1]);
+        prob.add_subset("C", vec![2]);
+        prob.add_subset("D", vec![3]);
+        prob.add_subset("E", vec![1, 2]);
+        prob.add_subset("F", vec![2, 3]);
+
+        let others = Solver::new(prob.clone()).verify_and_continue(vec!["D", "B", "C"]);
+        assert_eq!(others, Ok(vec![vec!["A"], vec!["B", "F"], vec!["E", "D"]]));
+
+        let invalid = Solver::new(prob.clone()).verify_and_continue(vec!["A", "D"]);
+        assert_eq!(invalid, Err(CandidateError::ViolatedConstraints(vec![3])));
+
+        let unknown = Solver::new(prob).verify_and_continue(vec!["X"]);
+        assert_eq!(unknown, Err(CandidateError::UnknownSubset("X")));
+    }
+
+    #[test]
+    fn solver_can_match_colored_edges() {
+        // Four tiles with edge colors [north, east, south, west] are placed in a 2x2 grid
+        // without rotation, and the internal edges of adjacent tiles must match.
+        let tiles = [[0, 1, 2, 0], [0, 0, 1, 1], [2, 1, 0, 0], [1, 0, 0, 1]];
+        // (position, [(edge, side)]) where the edges are named ('h', row) and ('v', col)
+        let edges = [
+            [(('h', 0), 1), (('v', 0), 2)],
+            [(('h', 0), 3), (('v', 1), 2)],
+            [(('h', 1), 1), (('v', 0), 0)],
+            [(('h', 1), 3), (('v', 1), 0)],
+        ];
+
+        let mut prob = Problem::default();
+        for i in 0..4 {
+            prob.add_exact_constraint(('p', i));
+            prob.add_exact_constraint(('t', i));
+            prob.add_secondary_constraint(('h', i % 2));
+            prob.add_secondary_constraint(('v', i % 2));
+        }
+        for (t, tile) in tiles.iter().enumerate() {
+            for (pos, pos_edges) in edges.iter().enumerate() {
+                let colors = pos_edges.iter().map(|&(edge, side)| (edge, tile[side])).collect();
+                prob.add_colored_subset((t, pos), vec![('p', pos), ('t', t)], colors);
+            }
+        }
+
+        let mut solutions: Vec<Vec<usize>> = Solver::new(prob).solve_blocking().unwrap()
+            .into_iter()
+            .map(|mut sol| {
+                sol.sort_unstable_by_key(|&(_, pos)| pos);
+                sol.into_iter().map(|(t, _)| t).collect()
+            })
+            .collect();
+        solutions.sort();
+
+        // Brute force over all permutations of the tiles
+        let mut expected = vec![];
+        for p in (0..4 * 4 * 4 * 4).map(|n| vec![n % 4, n / 4 % 4, n / 16 % 4, n / 64]) {
+            if (0..4).any(|i| !p.contains(&i)) { continue; }
+            let matches = |a: usize, side_a: usize, b: usize, side_b: usize| tiles[p[a]][side_a] == tiles[p[b]][side_b];
+            if matches(0, 1, 1, 3) && matches(2, 1, 3, 3) && matches(0, 2, 2, 0) && matches(1, 2, 3, 0) {
+                expected.push(p);
+            }
+        }
+
+        expected.sort();
+        assert!(!expected.is_empty() && expected.len() < 24);
+        assert_eq!(solutions, expected);
+    }
+
+    #[cfg(feature = "threads")]
+    fn pentomino_problem() -> Problem<CompoundName<&'static str>, CompoundConstraint<&'static str>> {
+        let mut prob = PolyominoPacking::default();
+        *prob.board_mut() = Board::from_bytes_array(&[
+            b"########",
+            b"########",
+            b"########",
+            b"###..###",
+            b"###..###",
+            b"########",
+            b"########",
+            b"########",
+        ]);
+
+        let pieces: [(&str, &[&[u8]]); 12] = [
+            ("F", &[b".##", b"##.", b".#."]),
+            ("I", &[b"#####"]),
+            ("L", &[b"####", b"#..."]),
+            ("N", &[b".###", b"##.."]),
+            ("P", &[b"###", b".##"]),
+            ("T", &[b"###", b".#.", b".#."]),
+            ("U", &[b"#.#", b"###"]),
+            ("V", &[b"#..", b"#..", b"###"]),
+            ("W", &[b"#..", b"##.", b".##"]),
+            ("X", &[b".#.", b"###", b".#."]),
+            ("Y", &[b"####", b".#.."]),
+            ("Z", &[b"##.", b".#.", b".##"]),
+        ];
+        for (name, array) in pieces {
+            prob.add_piece(name, Polyomino::from_bytes_array(array).unwrap());
+        }
+        prob.generate_problem()
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn paused_solver_reports_progress_and_resumes_in_place() {
+        let prob = pentomino_problem();
+        let expected = Solver::new(prob.clone()).solve_all().unwrap();
+
+        let mut solver = Solver::new(prob);
+        solver.run().unwrap();
+        let mut iter = solver.into_iter();
+        let mut solutions = vec![];
+        let mut paused_progress = vec![];
+        let mut paused = false;
+        while let Some(event) = iter.next() {
+            match event {
+                SolverEvent::SolutionFound(sol) => {
+                    assert!(!paused, "Found a solution while paused");
+                    if solutions.is_empty() { iter.solver().pause().unwrap(); }
+                    solutions.push(sol);
+                }
+                SolverEvent::Paused => {
+                    paused = true;
+                    iter.solver().request_progress().unwrap();
+                    iter.solver().request_progress().unwrap();
+                }
+                SolverEvent::ProgressUpdated { fraction, .. } if paused => {
+                    paused_progress.push(fraction);
+                    if paused_progress.len() == 2 {
+                        paused = false;
+                        iter.solver_mut().run().unwrap();
+                    }
+                }
+                // Only reported when the search finishes
+                SolverEvent::ProgressUpdated { fraction, .. } => assert_eq!(fraction, 1.0),
+                _ => (),
+            }
+        }
+
+        // The progress is frozen while paused
+        assert_eq!(paused_progress.len(), 2);
+        assert_eq!(paused_progress[0], paused_progress[1]);
+        assert!(0.0 < paused_progress[0] && paused_progress[0] < 1.0);
+        // No solution is skipped or repeated
+        assert_eq!(solutions, expected);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn solver_can_resume_from_aborted_matrix() {
+        use std::collections::HashSet;
+
+        let prob = pentomino_problem();
+
+        let mut solver = Solver::new(prob.clone());
+        solver.run().unwrap();
+        let expected: Vec<_> = solver.into_iter()
+            .filter_map(|event| match event {
+                SolverEvent::SolutionFound(sol) => Some(sol),
+                _ => None,
+            })
+            .collect();
+
+        // Pause and abort the solver as soon as the first solution is found
+        let mut solver = Solver::new(prob.clone());
+        let mut solutions = vec![];
+        let mut matrix = None;
+        solver.run().unwrap();
+
+        let mut iter = solver.into_iter();
+        while let Some(event) = iter.next() {
+            match event {
+                SolverEvent::SolutionFound(sol) => {
+                    if solutions.is_empty() {
+                        iter.solver().pause().unwrap();
+                        iter.solver().abort().unwrap();
+                    }
+                    solutions.push(sol);
+                }
+                SolverEvent::Aborted(mat) => matrix = Some(mat),
+                _ => (),
+            }
+        }
+        assert!(solutions.len() < expected.len());
+
+        // Resume the search and find the remaining solutions
+        let mut solver = Solver::resume(prob, matrix.unwrap());
+        solver.run().unwrap();
+        for event in solver {
+            if let SolverEvent::SolutionFound(sol) = event {
+                solutions.push(sol);
+            }
+        }
+
+        assert_eq!(solutions.len(), expected.len());
+        assert_eq!(
+            solutions.into_iter().collect::<HashSet<_>>(),
+            expected.into_iter().collect::<HashSet<_>>(),
+        );
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn abort_does_not_wait_for_poll_interval() {
+        // The abort flag does not speed up the search with the default poll interval,
+        // but it lets a larger interval skip polling the signals without delaying the abort.
+        let prob = pentomino_problem();
+        let expected = Solver::new(prob.clone()).solve_all().unwrap();
+
+        // The signals are never polled, but the abort still takes effect
+        let mut solver = Solver::new(prob.clone());
+        solver.set_poll_interval(usize::MAX);
+        solver.run().unwrap();
+
+        let mut solutions = vec![];
+        let mut matrix = None;
+        let mut iter = solver.into_iter();
+        while let Some(event) = iter.next() {
+            match event {
+                SolverEvent::SolutionFound(sol) => {
+                    if solutions.is_empty() { iter.solver().abort().unwrap(); }
+                    solutions.push(sol);
+                }
+                SolverEvent::Aborted(mat) => matrix = Some(mat),
+                _ => (),
+            }
+        }
+        assert!(solutions.len() < expected.len());
+
+        // Resuming the aborted search gives the same solutions in the same order
+        let mut solver = Solver::resume(prob, matrix.unwrap());
+        solver.set_poll_interval(usize::MAX);
+        solutions.extend(solver.solve_all().unwrap());
+        assert_eq!(solutions, expected);
+    }
 }
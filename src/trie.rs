@@ -0,0 +1,145 @@
+//! Provides a prefix trie that stores solutions compactly.
+
+use crate::problem::Value;
+
+/// A single node of [`SolutionTrie`].
+#[derive(Default, Clone)]
+#[cfg_attr(test, derive(Debug))]
+struct Node {
+    children: Vec<(usize, usize)>, // (subset index, node index)
+    terminal: bool, // whether the path to this node is a solution
+}
+
+/// A prefix trie of solutions keyed by subset indices.
+/// 
+/// Solutions sharing a common prefix share the nodes of that prefix,
+/// which saves a lot of memory when there are many similar solutions.
+/// The full solutions are reconstructed on demand by [`iter`](SolutionTrie::iter).
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug))]
+pub struct SolutionTrie<N: Value> {
+    names: Vec<N>, // subset names by index
+    nodes: Vec<Node>, // root: 0
+    len: usize,
+}
+
+impl<N: Value> SolutionTrie<N> {
+    /// Creates an empty trie for the subsets named `names`, in the order of their indices.
+    pub fn new(names: Vec<N>) -> SolutionTrie<N> {
+        SolutionTrie { names, nodes: vec![Node::default()], len: 0 }
+    }
+
+    /// Inserts a solution given as a sequence of subset indices.
+    pub fn insert<I: IntoIterator<Item = usize>>(&mut self, sol: I) {
+        let mut node = 0;
+        for subset in sol {
+            node = match self.nodes[node].children.iter().find(|&&(s, _)| s == subset) {
+                Some(&(_, child)) => child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[node].children.push((subset, child));
+                    child
+                }
+            };
+        }
+
+        if !self.nodes[node].terminal {
+            self.nodes[node].terminal = true;
+            self.len += 1;
+        }
+    }
+
+    /// Returns the number of solutions in the trie.
+    pub fn len(&self) -> usize { self.len }
+    /// Returns whether the trie has no solutions.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    /// Returns the number of nodes in the trie, including the root.
+    pub fn node_count(&self) -> usize { self.nodes.len() }
+
+    /// Returns an iterator that reconstructs the solutions in depth-first order.
+    /// 
+    /// The children of a node are visited in the order they were inserted,
+    /// and a solution comes before the solutions that extend it.
+    /// Note that the solver may find such an extension first (e.g. with optional constraints),
+    /// so the order can differ from the order of insertion.
+    pub fn iter(&self) -> SolutionTrieIter<'_, N> {
+        SolutionTrieIter {
+            trie: self,
+            stack: vec![(0, 0)],
+            path: vec![],
+            root_pending: self.nodes[0].terminal,
+        }
+    }
+
+    fn names_of(&self, path: &[usize]) -> Vec<N> {
+        path.iter().map(|&s| self.names[s].clone()).collect()
+    }
+}
+
+/// An iterator over the solutions of a [`SolutionTrie`].
+pub struct SolutionTrieIter<'a, N: Value> {
+    trie: &'a SolutionTrie<N>,
+    stack: Vec<(usize, usize)>, // (node index, next child position)
+    path: Vec<usize>,
+    root_pending: bool, // whether the empty solution is yet to be emitted
+}
+
+impl<'a, N: Value> Iterator for SolutionTrieIter<'a, N> {
+    type Item = Vec<N>;
+
+    fn next(&mut self) -> Option<Vec<N>> {
+        if self.root_pending {
+            self.root_pending = false;
+            return Some(vec![]);
+        }
+
+        loop {
+            let (node, pos) = self.stack.last_mut()?;
+            let children = &self.trie.nodes[*node].children;
+
+            if *pos < children.len() {
+                let (subset, child) = children[*pos];
+                *pos += 1;
+                self.path.push(subset);
+                self.stack.push((child, 0));
+
+                if self.trie.nodes[child].terminal {
+                    return Some(self.trie.names_of(&self.path));
+                }
+            } else {
+                self.stack.pop();
+                self.path.pop();
+            }
+        }
+    }
+}
+
+impl<'a, N: Value> IntoIterator for &'a SolutionTrie<N> {
+    type Item = Vec<N>;
+    type IntoIter = SolutionTrieIter<'a, N>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trie_shares_prefixes() {
+        let mut trie = SolutionTrie::new(vec!["A", "B", "C", "D"]);
+        trie.insert([0, 1]);
+        trie.insert([0, 2, 3]);
+        trie.insert([0, 2]);
+        trie.insert([0, 1]);
+
+        assert_eq!(trie.len(), 3);
+        assert_eq!(trie.node_count(), 5);
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            vec![vec!["A", "B"], vec!["A", "C"], vec!["A", "C", "D"]],
+        );
+    }
+}
@@ -1,4 +1,4 @@
-//! Basic Euclidean vector types.
+//! Basic Euclidean vector types, and coordinates of hexagonal grids.
 
 use std::ops;
 
@@ -24,14 +24,208 @@ impl ops::Sub<Vector2D> for Vector2D {
     }
 }
 
+impl ops::Mul<i32> for Vector2D {
+    type Output = Vector2D;
+    fn mul(self, rhs: i32) -> Vector2D {
+        Vector2D { x: self.x * rhs, y: self.y * rhs }
+    }
+}
+
+impl ops::Neg for Vector2D {
+    type Output = Vector2D;
+    fn neg(self) -> Vector2D {
+        Vector2D { x: -self.x, y: -self.y }
+    }
+}
+
 impl Vector2D {
+    /// The zero vector.
+    pub const ZERO: Vector2D = Vector2D { x: 0, y: 0 };
+
+    /// Returns the dot product of two vectors.
+    pub fn dot(&self, other: Vector2D) -> i32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Returns the Manhattan distance between two points.
+    pub fn manhattan(&self, other: Vector2D) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
     /// Rotates the vector by (rotation * 90) degrees.
     /// (counter-clockwise in the standard coordinate system where the y-axis is upwards.)
     pub fn rotate(&self, rotation: i32) -> Vector2D {
-        let t = ((rotation % 4 + 4) % 4) as usize;
+        let t = rotation.rem_euclid(4) as usize;
         Vector2D { 
             x: self.x * COS[t] - self.y * SIN[t],
             y: self.x * SIN[t] + self.y * COS[t],
         }
     }
 }
+
+/// A 3D vector to represent a coordinate, translation, etc.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Vector3D { pub x: i32, pub y: i32, pub z: i32 }
+
+impl ops::Add<Vector3D> for Vector3D {
+    type Output = Vector3D;
+    fn add(self, rhs: Vector3D) -> Vector3D {
+        Vector3D { x: self.x + rhs.x, y: self.y + rhs.y, z: self.z + rhs.z }
+    }
+}
+
+impl ops::Sub<Vector3D> for Vector3D {
+    type Output = Vector3D;
+    fn sub(self, rhs: Vector3D) -> Vector3D {
+        Vector3D { x: self.x - rhs.x, y: self.y - rhs.y, z: self.z - rhs.z }
+    }
+}
+
+impl Vector3D {
+    /// Rotates the vector around the x-axis by (rotation * 90) degrees.
+    /// (counter-clockwise when seen from the positive side of the axis.)
+    pub fn rotate_x(&self, rotation: i32) -> Vector3D {
+        let t = rotation.rem_euclid(4) as usize;
+        Vector3D {
+            x: self.x,
+            y: self.y * COS[t] - self.z * SIN[t],
+            z: self.y * SIN[t] + self.z * COS[t],
+        }
+    }
+
+    /// Rotates the vector around the y-axis by (rotation * 90) degrees.
+    /// (counter-clockwise when seen from the positive side of the axis.)
+    pub fn rotate_y(&self, rotation: i32) -> Vector3D {
+        let t = rotation.rem_euclid(4) as usize;
+        Vector3D {
+            x: self.x * COS[t] + self.z * SIN[t],
+            y: self.y,
+            z: -self.x * SIN[t] + self.z * COS[t],
+        }
+    }
+
+    /// Rotates the vector around the z-axis by (rotation * 90) degrees.
+    /// (counter-clockwise when seen from the positive side of the axis.)
+    pub fn rotate_z(&self, rotation: i32) -> Vector3D {
+        let t = rotation.rem_euclid(4) as usize;
+        Vector3D {
+            x: self.x * COS[t] - self.y * SIN[t],
+            y: self.x * SIN[t] + self.y * COS[t],
+            z: self.z,
+        }
+    }
+}
+
+/// An axial coordinate of a hexagonal grid, i.e. `q` and `r` of the cube coordinate `(q, -q - r, r)`.
+/// 
+/// Moving by `(1, 0)` and `(0, 1)` reaches two adjacent cells 60 degrees apart.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Hex { pub q: i32, pub r: i32 }
+
+impl ops::Add<Hex> for Hex {
+    type Output = Hex;
+    fn add(self, rhs: Hex) -> Hex {
+        Hex { q: self.q + rhs.q, r: self.r + rhs.r }
+    }
+}
+
+impl ops::Sub<Hex> for Hex {
+    type Output = Hex;
+    fn sub(self, rhs: Hex) -> Hex {
+        Hex { q: self.q - rhs.q, r: self.r - rhs.r }
+    }
+}
+
+impl ops::Neg for Hex {
+    type Output = Hex;
+    fn neg(self) -> Hex {
+        Hex { q: -self.q, r: -self.r }
+    }
+}
+
+impl Hex {
+    /// The origin.
+    pub const ZERO: Hex = Hex { q: 0, r: 0 };
+
+    /// Rotates the coordinate around the origin by (rotation * 60) degrees,
+    /// from `(1, 0)` toward `(0, 1)`.
+    pub fn rotate(&self, rotation: i32) -> Hex {
+        (0..rotation.rem_euclid(6)).fold(*self, |h, _| Hex { q: -h.r, r: h.q + h.r })
+    }
+
+    /// Reflects the coordinate across the line through the origin and `(1, 1)`,
+    /// i.e. swaps `q` and `r`.
+    pub fn reflect(&self) -> Hex {
+        Hex { q: self.r, r: self.q }
+    }
+
+    /// Returns the number of steps between two cells.
+    pub fn distance(&self, other: Hex) -> i32 {
+        let d = *self - other;
+        (d.q.abs() + d.r.abs() + (d.q + d.r).abs()) / 2
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: i32, y: i32) -> Vector2D { Vector2D { x, y } }
+
+    #[test]
+    fn vector_2d_arithmetic() {
+        assert_eq!(v(1, 2) + v(3, -4), v(4, -2));
+        assert_eq!(v(1, 2) - v(3, -4), v(-2, 6));
+        assert_eq!(v(1, -2) * 3, v(3, -6));
+        assert_eq!(-v(1, -2), v(-1, 2));
+        assert_eq!(v(1, -2) + Vector2D::ZERO, v(1, -2));
+        assert_eq!(v(1, -2) + -v(1, -2), Vector2D::ZERO);
+    }
+
+    #[test]
+    fn vector_2d_products_and_distances() {
+        assert_eq!(v(1, 2).dot(v(3, -4)), -5);
+        assert_eq!(v(1, 2).dot(v(1, 2).rotate(1)), 0);
+        assert_eq!(v(1, 2).manhattan(v(3, -4)), 8);
+        assert_eq!(v(3, -4).manhattan(v(1, 2)), 8);
+        assert_eq!(v(1, 2).manhattan(v(1, 2)), 0);
+    }
+
+    #[test]
+    fn vector_2d_rotations_compose() {
+        let a = v(2, 1);
+        assert_eq!(a.rotate(1), v(-1, 2));
+        assert_eq!(a.rotate(1).rotate(1), a.rotate(2));
+        assert_eq!(a.rotate(2), -a);
+        assert_eq!(a.rotate(3), a.rotate(-1));
+        assert_eq!(a.rotate(4), a);
+        for r in 0..4 {
+            assert_eq!((a * 2).rotate(r), a.rotate(r) * 2);
+            assert_eq!(a.rotate(r).manhattan(Vector2D::ZERO), a.manhattan(Vector2D::ZERO));
+        }
+    }
+
+    #[test]
+    fn hex_rotations_and_reflections_compose() {
+        let h = |q, r| Hex { q, r };
+        assert_eq!(h(1, 2) + h(3, -4) - h(1, 1), h(3, -3));
+        assert_eq!(-h(1, -2), h(-1, 2));
+
+        // The six neighbors of the origin in order
+        let neighbors: Vec<_> = (0..6).map(|r| h(1, 0).rotate(r)).collect();
+        assert_eq!(neighbors, vec![h(1, 0), h(0, 1), h(-1, 1), h(-1, 0), h(0, -1), h(1, -1)]);
+        assert!(neighbors.iter().all(|n| n.distance(Hex::ZERO) == 1));
+
+        let a = h(2, 1);
+        assert_eq!(a.rotate(3), -a);
+        assert_eq!(a.rotate(6), a);
+        assert_eq!(a.rotate(-1), a.rotate(5));
+        assert_eq!(a.reflect().reflect(), a);
+        // A reflection reverses the rotation
+        assert_eq!(a.rotate(1).reflect(), a.reflect().rotate(-1));
+        assert_eq!(a.distance(h(-1, 1)), 3);
+    }
+}